@@ -35,6 +35,13 @@
   )
 )
 
+/// What the loaded plugin supports: its `version`, the `shapemaker_version` of the
+/// generation algorithm, the `objects` and `fills` kinds, the built-in `themes`
+/// and the `parameters` of `shape_config` with their type and default.
+///
+/// -> dictionary
+#let plugin_info() = cbor(shapemaker.info())
+
 /// [INTERNAL] Get a semi unique seed for the document
 /// 
 /// -> int: The seed from the document layout
@@ -51,7 +58,92 @@
   return calc.rem(base_entropy * 1009 + date_entropy, 100000)
 }
 
-/// Generates a """""random""""" shape with the given parameters
+/// [INTERNAL] Turns a `"min..max"` string (the old range syntax) or a `(min, max)` array into an inclusive range array
+///
+/// -> array
+#let _range(r) = {
+  if type(r) == str {
+    r.split("..").map(int)
+  } else if type(r) == int {
+    (r, r)
+  } else {
+    r
+  }
+}
+
+/// [INTERNAL] Turns a `("blue", "cyan")` array or a `(blue: 3, cyan: 2)` dictionary into the plugin's `"blue:3,cyan:2"` palette string
+///
+/// -> str | none
+#let _colors(c) = {
+  if type(c) == array {
+    c.join(",")
+  } else if type(c) == dictionary {
+    c.pairs().map(((name, weight)) => name + ":" + str(weight)).join(",")
+  } else {
+    c
+  }
+}
+
+/// [INTERNAL] Turns a single probability into the same probability for every kind of object
+///
+/// -> dictionary | none
+#let _fill_probabilities(p) = {
+  if type(p) == int or type(p) == float {
+    (
+      polygon: p,
+      big_circle: p,
+      small_circle: p,
+      dot: p,
+      curve_inward: p,
+      curve_outward: p,
+      line: p,
+    )
+  } else {
+    p
+  }
+}
+
+/// [INTERNAL] Turns a single style into the same style for every kind of object
+///
+/// -> dictionary | none
+#let _object_styles(s) = {
+  let kinds = ("polygon", "big_circle", "small_circle", "dot", "curve_inward", "curve_outward", "line")
+  if type(s) == dictionary and s.keys().all(key => key not in kinds) {
+    kinds.map(kind => (kind, s)).to-dict()
+  } else {
+    s
+  }
+}
+
+/// [INTERNAL] Turns a single opacity or `(min, max)` range into ranges for every kind of object
+///
+/// -> dictionary | none
+#let _opacity_ranges(o) = {
+  let kinds = ("polygon", "big_circle", "small_circle", "dot", "curve_inward", "curve_outward", "line")
+  let range(r) = if type(r) == array { r } else { (r, r) }
+  if type(o) == dictionary {
+    o.pairs().map(((kind, r)) => (kind, range(r))).to-dict()
+  } else if o != none {
+    kinds.map(kind => (kind, range(o))).to-dict()
+  } else {
+    o
+  }
+}
+
+/// [INTERNAL] Turns `true` into an effect (or the sketchy style) with its default settings and `false` into none
+///
+/// -> dictionary | none
+#let _effect(e) = {
+  if e == true {
+    (:)
+  } else if e == false {
+    none
+  } else {
+    e
+  }
+}
+
+/// The plugin config for a shape with the given parameters
 ///
 /// - width_ratio (int): a stupid parameter, you can control the ratio of the width in relation to the height
 /// - cell_size (int): The canvas cell size
@@ -61,13 +153,36 @@
 /// - dot_radius (int): 
 /// - empty_shape_stroke (int): 
 /// - render_grid (bool): ?
-/// - objects_count (int): 
-/// - polygon_vertices (int): 
-/// - _seed (int | none): The final seed to pass down to the pulgin
+/// - objects_count (array): inclusive `(min, max)`, `"min..max"` still works
+/// - polygon_vertices (array): inclusive `(min, max)`, `"min..max"` still works
+/// - _seed (str | int | none): The final seed to pass down to the pulgin
 /// - color_theme (dict): The palette to pass down to the plugin (color mapping)
-/// - image_options (dict): Other options for the generated image
-/// -> image
-#let shape(
+/// - algorithm (str | none): The generation version, e.g. `"1.1"`, a version always draws the same shape for the same seed. `"1.3"` draws the curved edges of polygons as curves, `"1.4"` puts the center anchor at the actual center of the canvas, `"1.5"` draws lines `line_width` wide
+/// - object_weights (dict | none): How often each kind of object shows up, e.g. `(dot: 0, big_circle: 3)`. Kinds are `polygon`, `big_circle`, `small_circle`, `dot`, `curve_inward`, `curve_outward` and `line`, missing ones weigh 1
+/// - colors (array | dict | str | none): The only colors to pick from, e.g. `("blue", "cyan")`, with weights `(blue: 3, cyan: 2)` or `"blue:3,cyan:2"`. `none` picks from every color
+/// - fill_probabilities (dict | float | none): How likely each kind of object is to be filled (colored, for lines and curves), from 0 to 1, e.g. `(polygon: 0.8, dot: 1, big_circle: 0)`. A single number applies to every kind, missing kinds use 0.5
+/// - hatched_fill_probability (float): How likely a fill is to be hatched instead of solid
+/// - dotted_fill_probability (float): How likely a fill is to be dotted instead of solid, both add up to 1 at most
+/// - hatch_angle (int | float): Angle of the hatches in degrees, 0 is horizontal
+/// - hatch_spacing (int | float): Distance between two hatches
+/// - dotted_spacing (int | float): Distance between two dots of a dotted fill
+/// - dotted_radius (int | float): Radius of the dots of a dotted fill
+/// - curvature (float): How far curves bulge out of a straight line, relative to their length. Negative values bulge the other way
+/// - diagonal_curves_only (bool): Whether curves only go along diagonals of the grid, or from any anchor to any other
+/// - center_anchor_probability (float | none): How likely an anchor is to be the center of the canvas. `none` uses 1 over the number of anchors
+/// - symmetry (str): `"none"`, `"horizontal"` (left and right halves mirror each other), `"vertical"` (top and bottom halves), `"both"`, `"rotational2"` (unchanged by a half turn) or `"rotational4"` (by a quarter turn, needs a square grid). Looks best with `algorithm: "1.4"`, where the center anchor is on every axis
+/// - layering (str): The order objects are drawn in, the last on top: `"generation"`, `"large_to_small"`, `"filled_first"` (filled areas under outlines, lines and curves) or `"stroked_on_top"` (lines and curves over everything else)
+/// - object_styles (dict | none): How each kind of object is stroked, e.g. `(line: (linecap: "round", dasharray: (6, 3)), polygon: (stroke: "black", stroke_width: 2))`. Keys are `stroke` (a color name), `stroke_width`, `linecap` (`"butt"`, `"round"`, `"square"`), `linejoin` (`"miter"`, `"round"`, `"bevel"`), `dasharray` and `blend_mode`. A single style applies to every kind
+/// - opacity (int | float): Opacity of the whole shape, from 0 to 1
+/// - opacity_ranges (dict | array | float | none): The opacity each kind of object is drawn with, a `(min, max)` range picks one at random for every object, e.g. `(polygon: (0.5, 0.9), line: 1)`. A single value or range applies to every kind, missing kinds are opaque
+/// - blend_mode (str): How objects mix with what is under them, like CSS' `mix-blend-mode`: `"normal"`, `"multiply"` (overprinted inks), `"screen"`, `"overlay"`, `"darken"`, `"lighten"`, `"color-dodge"`, `"color-burn"`, `"hard-light"`, `"soft-light"`, `"difference"`, `"exclusion"`, `"hue"`, `"saturation"`, `"color"` or `"luminosity"`. `object_styles` can set it per kind with `blend_mode`
+/// - drop_shadow (dict | bool | none): A shadow of the theme's `black` under filled objects, e.g. `(dx: 2, dy: 2, blur: 2, opacity: 0.5)`, `true` uses these defaults
+/// - glow (dict | bool | none): A halo of their own color around lines, curves and outlines, e.g. `(radius: 3, opacity: 0.8)`, `true` uses these defaults
+/// - blur (dict | bool | none): Blurs the objects drawn first, e.g. `(radius: 2, layers: 1)` for the bottom one, `true` uses these defaults. Blurred objects get no other effect
+/// - grain (dict | bool | none): Paper-like noise of the theme's `background` color, e.g. `(intensity: 0.3, scale: 1.5, target: "canvas")`, `true` uses these defaults. `target` is `"canvas"` for the whole image or `"filled_objects"`
+/// - sketchy (dict | bool | none): Draws objects by hand, like rough.js, e.g. `(roughness: 1, bowing: 1, hachure_angle: -41, hachure_gap: 4, hachure_width: 1)`, `true` uses these defaults. Strokes are doubled and jittered, circles wobble and solid fills become hachures
+/// -> dictionary
+#let shape_config(
   width_ratio: 1,
   cell_size: 50,
   canvas_padding: 10,
@@ -75,17 +190,36 @@
   small_circle_radius: 15,
   dot_radius: 9,
   empty_shape_stroke: 1,
-  render_grid: "false",
-  objects_count: "3..4",
-  polygon_vertices: "3..5",
+  render_grid: false,
+  objects_count: (3, 4),
+  polygon_vertices: (3, 5),
   _seed: none,
   color_theme: color_themes.palenight,
-  image_options: ()
-) = context {
-  shape_index.step()
-
-  let seed = {calc.rem(get_compilation_seed() * 7919 + shape_index.get().at(0) * 43227, 1000)}
-
+  algorithm: none,
+  object_weights: none,
+  colors: none,
+  fill_probabilities: none,
+  hatched_fill_probability: 0,
+  dotted_fill_probability: 0,
+  hatch_angle: 45,
+  hatch_spacing: 4,
+  dotted_spacing: 5,
+  dotted_radius: 1,
+  curvature: 0.5,
+  diagonal_curves_only: true,
+  center_anchor_probability: none,
+  symmetry: "none",
+  layering: "generation",
+  object_styles: none,
+  opacity: 1,
+  opacity_ranges: none,
+  blend_mode: "normal",
+  drop_shadow: none,
+  glow: none,
+  blur: none,
+  grain: none,
+  sketchy: none,
+) = {
   let width
   let height
 
@@ -97,37 +231,158 @@
     height = 3
   }
 
+  (
+    grid_size: (int(width), int(height)),
+    cell_size: cell_size,
+    canvas_padding: canvas_padding,
+    line_width: line_width,
+    small_circle_radius: small_circle_radius,
+    dot_radius: dot_radius,
+    empty_shape_stroke: empty_shape_stroke,
+    render_grid: render_grid == true or render_grid == "true",
+    objects_count: _range(objects_count),
+    polygon_vertices: _range(polygon_vertices),
+    seed: _seed,
+    theme: color_theme,
+    algorithm: algorithm,
+    object_weights: object_weights,
+    colors: _colors(colors),
+    fill_probabilities: _fill_probabilities(fill_probabilities),
+    hatched_fill_probability: hatched_fill_probability,
+    dotted_fill_probability: dotted_fill_probability,
+    hatch_angle: hatch_angle,
+    hatch_spacing: hatch_spacing,
+    dotted_spacing: dotted_spacing,
+    dotted_radius: dotted_radius,
+    curvature: curvature,
+    diagonal_curves_only: diagonal_curves_only,
+    center_anchor_probability: center_anchor_probability,
+    symmetry: symmetry,
+    layering: layering,
+    object_styles: _object_styles(object_styles),
+    opacity: opacity,
+    opacity_ranges: _opacity_ranges(opacity_ranges),
+    blend_mode: blend_mode,
+    drop_shadow: _effect(drop_shadow),
+    glow: _effect(glow),
+    blur: _effect(blur),
+    grain: _effect(grain),
+    sketchy: _effect(sketchy),
+  )
+}
+
+/// [INTERNAL] `options` with the document seed filled in when no `_seed` was given
+///
+/// -> dictionary
+#let _seeded(options) = {
+  let options = options.named()
+  if options.at("_seed", default: none) == none {
+    let seed = {calc.rem(get_compilation_seed() * 7919 + shape_index.get().at(0) * 43227, 1000)}
+    options.insert("_seed", str(seed) + state("shape_seed").final())
+  }
+  options
+}
+
+/// Generates a """""random""""" shape with the given parameters
+///
+/// - options (dict): See `shape_config`
+/// - image_options (dict): Other options for the generated image
+/// -> image
+#let shape(image_options: (), ..options) = context {
+  shape_index.step()
+
   image( format: "svg",
-    shapemaker.svg(
-      bytes(str(width) + "x" + str(height)),
-      bytes(str(cell_size)),
-      bytes(str(canvas_padding)),
-      bytes(str(line_width)),
-      bytes(str(small_circle_radius)),
-      bytes(str(dot_radius)),
-      bytes(str(empty_shape_stroke)),
-      bytes(str(render_grid)),
-      bytes(str(objects_count)),
-      bytes(str(polygon_vertices)),
-      bytes(str(seed) + state("shape_seed").final()),
-      cbor.encode(color_theme)
-    ),
+    shapemaker.generate(cbor.encode(shape_config(.._seeded(options)))),
     ..image_options
   )
 }
 
-/// Self explanatory
+/// Draws the same shape as `shape` with some of its objects drawn again, the others stay put.
+/// Needs `algorithm: "1.2"` or later.
 ///
-/// - number (int): The number of shapes
-/// - options (dict): The shape options to pass down
+/// - rolls (array): How many times to re-roll each object, by index: `(0, 2)` re-rolls the second object twice
+/// - lock (array | none): Indices of the objects to keep, every other object is drawn as its `roll`-th variant
+/// - roll (int): The variant of the objects outside of `lock`
+/// - options (dict): See `shape_config`
+/// - image_options (dict): Other options for the generated image
+/// -> image
+#let reroll_shape(rolls: (), lock: none, roll: 1, image_options: (), ..options) = context {
+  shape_index.step()
+
+  let request = (
+    config: shape_config(.._seeded(options)),
+    rolls: rolls,
+    lock: lock,
+    roll: roll,
+  )
+
+  image( format: "svg",
+    shapemaker.reroll(cbor.encode(request)),
+    ..image_options
+  )
+}
+
+/// The shape `shape` would draw with the same options at the same place, as data
+///
+/// Each object has its `object` (kind and anchors), `fill`, `opacity` and `points`, the
+/// anchors in the same space as `view_box`. The `seed` and `render_seed` that were used are included.
+/// Without a `_seed`, the seed comes from the document like for `shape`, which needs a context:
+/// `context { let data = shape_data(); ... }`.
+///
+/// - options (dict): See `shape_config`
+/// -> dictionary
+#let shape_data(..options) = {
+  let config = shape_config(.._seeded(options))
+  cbor(shapemaker.shape_data(cbor.encode(config)))
+}
+
+/// Draws the given objects as they are, without any randomness
+///
+/// Objects look like the ones from `shape_data`, e.g.
+/// `(object: (CurveInward: ((Point: (0, 0)), (Point: (1, 1)))), fill: (Solid: "blue"))`.
+/// Available objects: `Polygon`, `Line`, `CurveInward`, `CurveOutward`,
+/// `SmallCircle`, `Dot` and `BigCircle`. Fills are `Solid`, `Hatched` or `Dotted`.
+/// Anchors are grid points `(Point: (x, y))`, cell centers `(Cell: (x, y))` or `"Center"`.
+/// An optional `opacity` from 0 to 1 overrides the `opacity_ranges` of the object.
+///
+/// - objects (array): The objects to draw, usually edited from `shape_data(..).objects`
+/// - render_seed (int | none): Seed of the `grain` noise and the `sketchy` jitter, e.g. `shape_data(..).render_seed`. `none` draws one from the seed
+/// - options (dict): See `shape_config`
+/// - image_options (dict): Other options for the generated image
+/// -> image
+#let render_shape(objects, render_seed: none, image_options: (), ..options) = {
+  let description = (
+    config: shape_config(..options),
+    objects: objects,
+    render_seed: render_seed,
+  )
+
+  image( format: "svg",
+    shapemaker.render(cbor.encode(description)),
+    ..image_options
+  )
+}
+
+/// Self explanatory, all the shapes come from a single plugin call
+///
+/// - number (int): The number of shapes, 1000 at most
+/// - unique (bool): Never draw the same composition twice in the strip
+/// - image_options (dict): Other options for each generated image
+/// - options (dict): The shape options to pass down, see `shape_config`
 /// -> A grid of shapes
-#let shape_strip(number: 1, ..options) = {
-  let shapes = range(number)
-  shapes = shapes.map(s => shape(..options))
+#let shape_strip(number: 1, unique: true, image_options: (), ..options) = context {
+  shape_index.step()
+
+  let request = (
+    config: shape_config(.._seeded(options)),
+    count: number,
+    unique: unique,
+  )
+  let shapes = cbor(shapemaker.generate_batch(cbor.encode(request)))
 
   grid(
     columns: number,
-    ..shapes.map(shape => [ #shape ]),
+    ..shapes.map(svg => [ #image(bytes(svg), format: "svg", ..image_options) ]),
   )
 }
 
@@ -2,9 +2,24 @@ use crate::color_mapping::ColorMapping;
 use crate::shape::Shape;
 use crate::shape::*;
 use crate::color::*;
+use crate::error::{Error, Result};
+use crate::generation::GenerationVersion;
+use crate::rng::{derive_seed, seeded};
+use crate::layering::Layering;
+use crate::effects::{self, Blur, DropShadow, Glow, Grain};
+use crate::sketch::{self, Sketchy};
+use crate::style::{BlendMode, ObjectStyles, OpacityRanges};
+use crate::symmetry::Symmetry;
+use serde::{Deserialize, Serialize};
 use std::ops::Range;
 use rand::Rng;
 
+/// Most shapes `Canvas::random_batch` draws at once, unique ones are compared to every
+/// shape found before them.
+pub const MAX_BATCH_COUNT: usize = 1000;
+
+/// How many seeds per requested shape `Canvas::random_batch` tries before giving up on `unique`.
+pub const UNIQUE_ATTEMPTS_PER_SHAPE: usize = 50;
 
 #[derive(Debug, Clone)]
 pub struct Canvas {
@@ -19,6 +34,195 @@ pub struct Canvas {
     pub dot_radius: f32,
     pub render_grid: bool,
     pub colormap: ColorMapping,
+    pub algorithm: GenerationVersion,
+    pub object_weights: ObjectWeights,
+    /// Colors `random_color` picks from with their weights, all of them when empty.
+    pub palette: Vec<(Color, f64)>,
+    pub fill_probabilities: FillProbabilities,
+    /// How likely a fill is to be `Fill::Hatched` or `Fill::Dotted` instead of solid.
+    pub hatched_fill_probability: f64,
+    pub dotted_fill_probability: f64,
+    /// Angle of hatches in degrees, 0 is horizontal.
+    pub hatch_angle: f32,
+    /// Distance between two hatches, their width is a quarter of it.
+    pub hatch_spacing: f32,
+    pub dotted_spacing: f32,
+    pub dotted_radius: f32,
+    /// How far curves bulge from a straight line, relative to their length.
+    pub curvature: f32,
+    /// Whether curves only go along diagonals, or to any other anchor.
+    pub diagonal_curves_only: bool,
+    /// How likely a random anchor is to be the canvas center. `None` uses 1 over the
+    /// number of points (or cells, for big circles), the probability every version started with.
+    pub center_anchor_probability: Option<f64>,
+    pub symmetry: Symmetry,
+    pub layering: Layering,
+    /// Stroke color, width, caps, joins and dashes of each kind of object.
+    pub object_styles: ObjectStyles,
+    /// Opacity of the whole drawing, every object's own opacity is multiplied by it.
+    pub opacity: f32,
+    pub opacity_ranges: OpacityRanges,
+    pub blend_mode: BlendMode,
+    /// Filter effects, `None` when disabled, see the `effects` module.
+    pub drop_shadow: Option<DropShadow>,
+    pub glow: Option<Glow>,
+    pub blur: Option<Blur>,
+    pub grain: Option<Grain>,
+    /// Draws objects by hand instead of crisp, see the `sketch` module.
+    pub sketchy: Option<Sketchy>,
+}
+
+/// How likely `random_object` is to pick each kind of object, relative to the others.
+/// Kinds with a weight of zero are never picked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectWeights {
+    #[serde(deserialize_with = "crate::config::number")]
+    pub polygon: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub big_circle: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub small_circle: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub dot: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub curve_inward: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub curve_outward: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub line: f64,
+}
+
+impl Default for ObjectWeights {
+    fn default() -> Self {
+        ObjectWeights {
+            polygon: 1.0,
+            big_circle: 1.0,
+            small_circle: 1.0,
+            dot: 1.0,
+            curve_inward: 1.0,
+            curve_outward: 1.0,
+            line: 1.0,
+        }
+    }
+}
+
+impl ObjectWeights {
+    /// In the order of `random_object`'s uniform draw.
+    fn as_array(&self) -> [f64; 7] {
+        [
+            self.polygon,
+            self.big_circle,
+            self.small_circle,
+            self.dot,
+            self.curve_inward,
+            self.curve_outward,
+            self.line,
+        ]
+    }
+
+    /// Picks a kind, numbered like `random_object`'s uniform draw (1 to 7).
+    /// Equal weights keep that exact uniform draw, so that every generation version
+    /// draws the same shapes with the default weights.
+    fn pick(&self, rng: &mut impl Rng) -> i32 {
+        let weights = self.as_array();
+        if weights.iter().all(|weight| *weight == weights[0]) {
+            return rng.gen_range(1..=7);
+        }
+        weighted_index(rng, &weights) as i32 + 1
+    }
+}
+
+/// How likely each kind of object is to get a fill, from 0 (never) to 1 (always).
+/// Lines and curves are never filled, their fill is the color they are stroked with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FillProbabilities {
+    #[serde(deserialize_with = "crate::config::number")]
+    pub polygon: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub big_circle: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub small_circle: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub dot: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub curve_inward: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub curve_outward: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub line: f64,
+}
+
+impl Default for FillProbabilities {
+    fn default() -> Self {
+        FillProbabilities {
+            polygon: 0.5,
+            big_circle: 0.5,
+            small_circle: 0.5,
+            dot: 0.5,
+            curve_inward: 0.5,
+            curve_outward: 0.5,
+            line: 0.5,
+        }
+    }
+}
+
+impl FillProbabilities {
+    pub fn of(&self, object: &Object) -> f64 {
+        match object {
+            Object::Polygon(..) => self.polygon,
+            Object::BigCircle(..) => self.big_circle,
+            Object::SmallCircle(..) => self.small_circle,
+            Object::Dot(..) => self.dot,
+            Object::CurveInward(..) => self.curve_inward,
+            Object::CurveOutward(..) => self.curve_outward,
+            Object::Line(..) => self.line,
+        }
+    }
+
+    fn as_array(&self) -> [f64; 7] {
+        [
+            self.polygon,
+            self.big_circle,
+            self.small_circle,
+            self.dot,
+            self.curve_inward,
+            self.curve_outward,
+            self.line,
+        ]
+    }
+}
+
+/// Index picked with a probability proportional to its weight, weights need a positive sum.
+fn weighted_index(rng: &mut impl Rng, weights: &[f64]) -> usize {
+    let mut remaining = rng.gen_range(0.0..weights.iter().sum::<f64>());
+    for (index, weight) in weights.iter().enumerate() {
+        if remaining < *weight {
+            return index;
+        }
+        remaining -= weight;
+    }
+    // Only reachable through rounding, fall back on the last index that can be picked
+    weights.iter().rposition(|weight| *weight > 0.0).unwrap_or(0)
+}
+
+/// Errors unless every weight is zero or more, at least one is above zero
+/// and their sum is a number, which `weighted_index` draws below.
+fn validate_weights(parameter: &str, weights: &[f64]) -> Result<()> {
+    if weights.iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+        return Err(Error::invalid(
+            parameter,
+            format!("weights must be zero or more, got {:?}", weights),
+        ));
+    }
+    if !weights.iter().any(|weight| *weight > 0.0) {
+        return Err(Error::invalid(parameter, "at least one weight needs to be above zero"));
+    }
+    if !weights.iter().sum::<f64>().is_finite() {
+        return Err(Error::invalid(parameter, format!("weights are too large, got {:?}", weights)));
+    }
+    Ok(())
 }
 
 impl Canvas {
@@ -35,86 +239,357 @@ impl Canvas {
             dot_radius: 2.0,
             render_grid: false,
             colormap: ColorMapping::default(),
+            algorithm: GenerationVersion::default(),
+            object_weights: ObjectWeights::default(),
+            palette: vec![],
+            fill_probabilities: FillProbabilities::default(),
+            hatched_fill_probability: 0.0,
+            dotted_fill_probability: 0.0,
+            hatch_angle: 45.0,
+            hatch_spacing: 4.0,
+            dotted_spacing: 5.0,
+            dotted_radius: 1.0,
+            curvature: 0.5,
+            diagonal_curves_only: true,
+            center_anchor_probability: None,
+            symmetry: Symmetry::None,
+            layering: Layering::Generation,
+            object_styles: ObjectStyles::default(),
+            opacity: 1.0,
+            opacity_ranges: OpacityRanges::default(),
+            blend_mode: BlendMode::Normal,
+            drop_shadow: None,
+            glow: None,
+            blur: None,
+            grain: None,
+            sketchy: None,
+        }
+    }
+    /// Size of the rendered image, padding included.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (
+            self.cell_size * (self.grid_size.0 - 1) + 2 * self.canvas_outter_padding,
+            self.cell_size * (self.grid_size.1 - 1) + 2 * self.canvas_outter_padding,
+        )
+    }
+
+    /// Checks the settings that would otherwise make generation panic.
+    pub fn validate(&self) -> Result<()> {
+        if self.grid_size.0 == 0 || self.grid_size.1 == 0 {
+            return Err(Error::invalid(
+                "grid_size",
+                format!(
+                    "the grid needs at least one anchor, got {}x{}",
+                    self.grid_size.0, self.grid_size.1
+                ),
+            ));
+        }
+        validate_weights("object_weights", &self.object_weights.as_array())?;
+        if !self.palette.is_empty() {
+            let weights: Vec<f64> = self.palette.iter().map(|(_, weight)| *weight).collect();
+            validate_weights("colors", &weights)?;
+        }
+        let patterned = [self.hatched_fill_probability, self.dotted_fill_probability];
+        if patterned.iter().any(|p| !(0.0..=1.0).contains(p)) || patterned.iter().sum::<f64>() > 1.0 {
+            return Err(Error::invalid(
+                "hatched_fill_probability",
+                format!(
+                    "hatched and dotted fill probabilities must be between 0 and 1 and add up to 1 at most, got {} and {}",
+                    self.hatched_fill_probability, self.dotted_fill_probability
+                ),
+            ));
+        }
+        if let Some(center_anchor_probability) = self.center_anchor_probability {
+            if !(0.0..=1.0).contains(&center_anchor_probability) {
+                return Err(Error::invalid(
+                    "center_anchor_probability",
+                    format!("must be between 0 and 1, got {}", center_anchor_probability),
+                ));
+            }
+        }
+        if self.symmetry == Symmetry::Rotational4 && self.grid_size.0 != self.grid_size.1 {
+            return Err(Error::invalid(
+                "symmetry",
+                format!(
+                    "quarter turns need a square grid, got {}x{}",
+                    self.grid_size.0, self.grid_size.1
+                ),
+            ));
+        }
+        if !self.curvature.is_finite() {
+            return Err(Error::invalid("curvature", format!("must be a number, got {}", self.curvature)));
+        }
+        for (parameter, value) in [
+            ("hatch_spacing", self.hatch_spacing),
+            ("dotted_spacing", self.dotted_spacing),
+            ("dotted_radius", self.dotted_radius),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(Error::invalid(parameter, format!("must be above zero, got {}", value)));
+            }
+        }
+        if !self.hatch_angle.is_finite() {
+            return Err(Error::invalid("hatch_angle", format!("must be a number, got {}", self.hatch_angle)));
+        }
+        if !(0.0..=1.0).contains(&self.opacity) {
+            return Err(Error::invalid("opacity", format!("must be between 0 and 1, got {}", self.opacity)));
+        }
+        let opacity_ranges = self.opacity_ranges.as_array();
+        if opacity_ranges
+            .iter()
+            .any(|(min, max)| !(0.0..=1.0).contains(min) || !(0.0..=1.0).contains(max) || min > max)
+        {
+            return Err(Error::invalid(
+                "opacity_ranges",
+                format!(
+                    "ranges must be between 0 and 1 with their minimum first, got {:?}",
+                    opacity_ranges
+                ),
+            ));
+        }
+        effects::validate(self)?;
+        sketch::validate(self)?;
+        let fill_probabilities = self.fill_probabilities.as_array();
+        if fill_probabilities.iter().any(|p| !(0.0..=1.0).contains(p)) {
+            return Err(Error::invalid(
+                "fill_probabilities",
+                format!("probabilities must be between 0 and 1, got {:?}", fill_probabilities),
+            ));
+        }
+        for (parameter, range) in [
+            ("objects_count", &self.objects_count_range),
+            ("polygon_vertices", &self.polygon_vertices_range),
+        ] {
+            if range.is_empty() {
+                return Err(Error::invalid(
+                    parameter,
+                    format!(
+                        "the range {}..={} is empty, its minimum must not exceed its maximum",
+                        range.start,
+                        range.end as i64 - 1
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn random_shape(&self, rng: &mut impl Rng) -> Result<Shape> {
+        self.validate()?;
+        match self.algorithm {
+            GenerationVersion::V1_1 => Ok(self.symmetry.apply(self.random_shape_v1_1(rng), self)),
+            GenerationVersion::V1_2
+            | GenerationVersion::V1_3
+            | GenerationVersion::V1_4
+            | GenerationVersion::V1_5 => {
+                self.random_shape_rolled(rng, |_| 0)
+            }
+        }
+    }
+
+    /// `count` shapes, each one drawn from its own seed derived from `seed` and its position.
+    /// With `unique`, seeds are tried until no two shapes have the same structure, see
+    /// `Shape::same_structure`, giving up after `UNIQUE_ATTEMPTS_PER_SHAPE` seeds per shape.
+    /// At most `MAX_BATCH_COUNT` shapes are drawn at once.
+    pub fn random_batch(&self, seed: u64, count: usize, unique: bool) -> Result<Vec<Shape>> {
+        let max_attempts = if unique {
+            count.checked_mul(UNIQUE_ATTEMPTS_PER_SHAPE)
+        } else {
+            Some(count)
+        };
+        let max_attempts = match max_attempts {
+            Some(max_attempts) if count <= MAX_BATCH_COUNT => max_attempts,
+            _ => {
+                return Err(Error::invalid(
+                    "count",
+                    format!("at most {} shapes can be drawn at once, got {}", MAX_BATCH_COUNT, count),
+                ))
+            }
+        };
+
+        let mut shapes: Vec<Shape> = vec![];
+        for attempt in 0..max_attempts {
+            if shapes.len() == count {
+                break;
+            }
+            let shape = self.random_shape(&mut seeded(derive_seed(seed, attempt as u64)))?;
+            if unique && shapes.iter().any(|other| other.same_structure(&shape)) {
+                continue;
+            }
+            shapes.push(shape);
+        }
+        if shapes.len() < count {
+            return Err(Error::invalid(
+                "unique",
+                format!(
+                    "only found {} different shapes out of {} after {} attempts, \
+                     try a bigger grid_size or objects_count",
+                    shapes.len(),
+                    count,
+                    max_attempts
+                ),
+            ));
         }
+        Ok(shapes)
     }
-    pub fn random_shape(&self) -> Shape {
+
+    fn random_shape_v1_1(&self, rng: &mut impl Rng) -> Shape {
         let mut objects: Vec<(Object, Option<Fill>)> = vec![];
-        let number_of_objects = rand::thread_rng().gen_range(self.objects_count_range.clone());
+        let mut opacities = vec![];
+        let number_of_objects = rng.gen_range(self.objects_count_range.clone());
         for _ in 0..number_of_objects {
-            let object = self.random_object();
-            objects.push((
-                object,
-                if rand::thread_rng().gen_bool(0.5) {
-                    Some(self.random_fill())
-                } else {
-                    None
-                },
+            let (object, fill, opacity) = self.random_filled_object(rng);
+            objects.push((object, fill));
+            opacities.push(opacity);
+        }
+        Shape {
+            objects,
+            opacities,
+            render_seed: self.random_render_seed(rng),
+        }
+    }
+
+    /// Draws a shape where every object comes from its own sub-seed, so that one object
+    /// can be drawn again without touching the others: object `k` is drawn as its
+    /// `roll(k)`-th variant, the shape `random_shape` draws is every object's 0th.
+    /// Needs an algorithm with per-object seeds (1.2 or later).
+    pub fn random_shape_rolled(
+        &self,
+        rng: &mut impl Rng,
+        roll: impl Fn(usize) -> u64,
+    ) -> Result<Shape> {
+        self.validate()?;
+        if self.algorithm < GenerationVersion::V1_2 {
+            return Err(Error::invalid(
+                "algorithm",
+                format!(
+                    "re-rolling objects needs per-object seeds, which version {} does not have, use 1.2 or later",
+                    self.algorithm.name()
+                ),
             ));
         }
-        Shape { objects }
+        // The base seed comes first, so that changing `objects_count` keeps every object.
+        let base_seed: u64 = rng.gen();
+        let number_of_objects = rng.gen_range(self.objects_count_range.clone());
+        let (objects, opacities) = (0..number_of_objects)
+            .map(|index| {
+                let (object, fill, opacity) = self.random_object_slot(base_seed, index, roll(index));
+                ((object, fill), opacity)
+            })
+            .unzip();
+        let shape = Shape {
+            objects,
+            opacities,
+            render_seed: self.random_render_seed(rng),
+        };
+        Ok(self.symmetry.apply(shape, self))
+    }
+
+    /// The `roll`-th variant of the object at `index` in a shape with per-object seeds,
+    /// with its fill and opacity.
+    pub fn random_object_slot(&self, base_seed: u64, index: usize, roll: u64) -> (Object, Option<Fill>, f32) {
+        let slot_seed = derive_seed(derive_seed(base_seed, index as u64), roll);
+        self.random_filled_object(&mut seeded(slot_seed))
     }
 
-    pub fn random_object(&self) -> Object {
-        let start = self.random_anchor();
-        match rand::thread_rng().gen_range(1..=7) {
-            1 => self.random_polygon(),
-            2 => Object::BigCircle(self.random_center_anchor()),
+    fn random_filled_object(&self, rng: &mut impl Rng) -> (Object, Option<Fill>, f32) {
+        let object = self.random_object(rng);
+        // 0.5 is the exact coin every version used to flip
+        let fill = if rng.gen_bool(self.fill_probabilities.of(&object)) {
+            Some(self.random_fill(rng))
+        } else {
+            None
+        };
+        let opacity = self.random_opacity(rng, &object);
+        (object, fill, opacity)
+    }
+
+    /// Seed of the randomness of rendering, drawn after everything else so that it does
+    /// not change the shape. Zero without grain or sketchy rendering, which draws nothing.
+    pub fn random_render_seed(&self, rng: &mut impl Rng) -> u64 {
+        if self.grain.is_some() || self.sketchy.is_some() {
+            rng.gen()
+        } else {
+            0
+        }
+    }
+
+    /// Draws from the opacity range of the object's kind, single values are not drawn.
+    pub fn random_opacity(&self, rng: &mut impl Rng, object: &Object) -> f32 {
+        match self.opacity_ranges.of(object) {
+            (min, max) if min < max => rng.gen_range(min..=max),
+            (min, _) => min,
+        }
+    }
+
+    pub fn random_object(&self, rng: &mut impl Rng) -> Object {
+        let start = self.random_anchor(rng);
+        match self.object_weights.pick(rng) {
+            1 => self.random_polygon(rng),
+            2 => match self.random_cell_anchor(rng) {
+                Some(center) => Object::BigCircle(center),
+                // Grids one anchor wide or high have no cell to put a big circle in
+                None => Object::SmallCircle(start),
+            },
             3 => Object::SmallCircle(start),
             4 => Object::Dot(start),
-            5 => Object::CurveInward(start, self.random_end_anchor(start)),
-            6 => Object::CurveOutward(start, self.random_end_anchor(start)),
-            7 => Object::Line(self.random_anchor(), self.random_anchor()),
+            kind @ (5 | 6) => match self.random_end_anchor(rng, start) {
+                Some(end) if kind == 5 => Object::CurveInward(start, end),
+                Some(end) => Object::CurveOutward(start, end),
+                // The start is the only anchor of the grid
+                None => Object::Dot(start),
+            },
+            7 => Object::Line(self.random_anchor(rng), self.random_anchor(rng)),
             _ => unreachable!(),
         }
     }
 
-    pub fn random_end_anchor(&self, start: Anchor) -> Anchor {
-        // End anchors are always a square diagonal from the start anchor (for now)
+    /// `None` when the grid has no other anchor than `start`.
+    pub fn random_end_anchor(&self, rng: &mut impl Rng, start: Anchor) -> Option<Anchor> {
+        let mut possible_end_anchors = self.possible_end_anchors(start, self.diagonal_curves_only);
+        if possible_end_anchors.is_empty() {
+            // Grids one anchor wide have no diagonal to follow, go straight instead
+            possible_end_anchors = self.possible_end_anchors(start, false);
+        }
+        if possible_end_anchors.is_empty() {
+            return None;
+        }
+
+        // Pick a random end anchor from the possible end anchors
+        Some(possible_end_anchors[rng.gen_range(0..possible_end_anchors.len())])
+    }
+
+    fn possible_end_anchors(&self, start: Anchor, diagonal_only: bool) -> Vec<Anchor> {
+        // End anchors are a square diagonal from the start anchor, unless `diagonal_only` is off
         // that means taking steps of the form n * (one of (1, 1), (1, -1), (-1, 1), (-1, -1))
         // Except that the end anchor needs to stay in the bounds of the shape.
-
-        // Determine all possible end anchors that are in a square diagonal from the start anchor
+        // Positions are doubled so that diagonals from cell centers or the canvas center work too.
+        let (start_x, start_y) = start.doubled(self);
         let mut possible_end_anchors = vec![];
-        let grid_width = self.grid_size.0 as i32;
-        let grid_height = self.grid_size.1 as i32;
-
-        for x in -grid_width..=grid_width {
-            for y in -grid_height..=grid_height {
-                let end_anchor = Anchor(start.0 + x, start.1 + y);
-
-                if end_anchor == start {
-                    continue;
-                }
-
-                // Check that the end anchor is in a square diagonal from the start anchor and that the end anchor is in bounds
-                if x.abs() == y.abs()
-                    && end_anchor.0.abs() < grid_width
-                    && end_anchor.1.abs() < grid_height
-                    && end_anchor.0 >= 0
-                    && end_anchor.1 >= 0
-                {
-                    possible_end_anchors.push(end_anchor);
+        for i in 0..self.grid_size.0 as i32 {
+            for j in 0..self.grid_size.1 as i32 {
+                let (x, y) = (2 * i - start_x, 2 * j - start_y);
+                if (x, y) != (0, 0) && (x.abs() == y.abs() || !diagonal_only) {
+                    possible_end_anchors.push(Anchor::Point(i, j));
                 }
             }
         }
-
-        // Pick a random end anchor from the possible end anchors
-        possible_end_anchors[rand::thread_rng().gen_range(0..possible_end_anchors.len())]
+        possible_end_anchors
     }
 
-    pub fn random_polygon(&self) -> Object {
-        let number_of_anchors = rand::thread_rng().gen_range(self.polygon_vertices_range.clone());
-        let start = self.random_anchor();
+    pub fn random_polygon(&self, rng: &mut impl Rng) -> Object {
+        let number_of_anchors = rng.gen_range(self.polygon_vertices_range.clone());
+        let start = self.random_anchor(rng);
         let mut lines: Vec<Line> = vec![];
         for _ in 0..number_of_anchors {
-            let next_anchor = self.random_anchor();
-            lines.push(self.random_line(next_anchor));
+            let next_anchor = self.random_anchor(rng);
+            lines.push(self.random_line(rng, next_anchor));
         }
         Object::Polygon(start, lines)
     }
 
-    pub fn random_line(&self, end: Anchor) -> Line {
-        match rand::thread_rng().gen_range(1..=3) {
+    pub fn random_line(&self, rng: &mut impl Rng, end: Anchor) -> Line {
+        match rng.gen_range(1..=3) {
             1 => Line::Line(end),
             2 => Line::InwardCurve(end),
             3 => Line::OutwardCurve(end),
@@ -122,44 +597,65 @@ impl Canvas {
         }
     }
 
-    pub fn random_anchor(&self) -> Anchor {
-        if rand::thread_rng().gen_bool(1.0 / (self.grid_size.0 * self.grid_size.1) as f64) {
-            // small change of getting center (-1, -1) even when grid size would not permit it (e.g. 4x4)
-            Anchor(-1, -1)
+    pub fn random_anchor(&self, rng: &mut impl Rng) -> Anchor {
+        if self.grid_size.0 == 0 || self.grid_size.1 == 0 {
+            // No anchor to pick from, `validate` refuses to generate shapes on such grids anyway
+            return Anchor::Center;
+        }
+        let center_probability = self
+            .center_anchor_probability
+            .unwrap_or(1.0 / (self.grid_size.0 * self.grid_size.1) as f64);
+        if rng.gen_bool(center_probability) {
+            Anchor::Center
         } else {
-            Anchor(
-                rand::thread_rng().gen_range(0..=self.grid_size.0 - 1) as i32,
-                rand::thread_rng().gen_range(0..=self.grid_size.1 - 1) as i32,
+            Anchor::Point(
+                rng.gen_range(0..=self.grid_size.0 - 1) as i32,
+                rng.gen_range(0..=self.grid_size.1 - 1) as i32,
             )
         }
     }
 
-    pub fn random_center_anchor(&self) -> CenterAnchor {
-        if rand::thread_rng()
-            .gen_bool(1.0 / ((self.grid_size.0 as i32 - 1) * (self.grid_size.1 as i32 - 1)) as f64)
-        {
-            // small change of getting center (-1, -1) even when grid size would not permit it (e.g. 3x3)
-            CenterAnchor(-1, -1)
+    /// The center of a cell or of the canvas, `None` when the grid has no cell,
+    /// i.e. is a single anchor wide or high.
+    pub fn random_cell_anchor(&self, rng: &mut impl Rng) -> Option<Anchor> {
+        if self.grid_size.0 < 2 || self.grid_size.1 < 2 {
+            return None;
+        }
+        let center_probability = self
+            .center_anchor_probability
+            .unwrap_or(1.0 / ((self.grid_size.0 - 1) * (self.grid_size.1 - 1)) as f64);
+        Some(if rng.gen_bool(center_probability) {
+            Anchor::Center
         } else {
-            CenterAnchor(
-                rand::thread_rng().gen_range(0..=self.grid_size.0 - 2) as i32,
-                rand::thread_rng().gen_range(0..=self.grid_size.1 - 2) as i32,
+            Anchor::Cell(
+                rng.gen_range(0..=self.grid_size.0 - 2) as i32,
+                rng.gen_range(0..=self.grid_size.1 - 2) as i32,
             )
-        }
+        })
     }
 
-    pub fn random_fill(&self) -> Fill {
-        Fill::Solid(self.random_color())
-        // match rand::thread_rng().gen_range(1..=3) {
-        //     1 => Fill::Solid(random_color()),
-        //     2 => Fill::Hatched,
-        //     3 => Fill::Dotted,
-        //     _ => unreachable!(),
-        // }
+    pub fn random_fill(&self, rng: &mut impl Rng) -> Fill {
+        // Without patterns, keep the single color draw every version was made with
+        if self.hatched_fill_probability + self.dotted_fill_probability <= 0.0 {
+            return Fill::Solid(self.random_color(rng));
+        }
+        let pattern = rng.gen_range(0.0..1.0);
+        let color = self.random_color(rng);
+        if pattern < self.hatched_fill_probability {
+            Fill::Hatched(color)
+        } else if pattern < self.hatched_fill_probability + self.dotted_fill_probability {
+            Fill::Dotted(color)
+        } else {
+            Fill::Solid(color)
+        }
     }
 
-    pub fn random_color(&self) -> Color {
-        match rand::thread_rng().gen_range(1..=12) {
+    pub fn random_color(&self, rng: &mut impl Rng) -> Color {
+        if !self.palette.is_empty() {
+            let weights: Vec<f64> = self.palette.iter().map(|(_, weight)| *weight).collect();
+            return self.palette[weighted_index(rng, &weights)].0;
+        }
+        match rng.gen_range(1..=12) {
             1 => Color::Black,
             2 => Color::White,
             3 => Color::Red,
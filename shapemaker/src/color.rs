@@ -1,6 +1,9 @@
 use crate::color_mapping::ColorMapping;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Color {
     Black,
     White,
@@ -17,6 +20,43 @@ pub enum Color {
 }
 
 impl Color {
+    pub const ALL: [Color; 12] = [
+        Color::Black,
+        Color::White,
+        Color::Red,
+        Color::Green,
+        Color::Blue,
+        Color::Yellow,
+        Color::Orange,
+        Color::Purple,
+        Color::Brown,
+        Color::Cyan,
+        Color::Pink,
+        Color::Gray,
+    ];
+
+    /// The name of the color in a `ColorMapping`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Color::Black => "black",
+            Color::White => "white",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Blue => "blue",
+            Color::Yellow => "yellow",
+            Color::Orange => "orange",
+            Color::Purple => "purple",
+            Color::Brown => "brown",
+            Color::Cyan => "cyan",
+            Color::Pink => "pink",
+            Color::Gray => "gray",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Color> {
+        Color::ALL.into_iter().find(|color| color.name() == name)
+    }
+
     pub fn to_string(self, mapping: &ColorMapping) -> String {
         match self {
             Color::Black => mapping.black.to_string(),
@@ -35,9 +75,69 @@ impl Color {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Parses a palette like `blue:3,cyan:2,black`, a color without a weight weighs 1.
+pub fn parse_palette(parameter: &str, spec: &str) -> Result<Vec<(Color, f64)>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, weight) = match entry.split_once(':') {
+                Some((name, weight)) => (name.trim(), Some(weight.trim())),
+                None => (entry, None),
+            };
+            let color = Color::from_name(&name.to_lowercase()).ok_or_else(|| {
+                Error::invalid(
+                    parameter,
+                    format!(
+                        "unknown color {:?}, colors are {}",
+                        name,
+                        Color::ALL.map(Color::name).join(", ")
+                    ),
+                )
+            })?;
+            let weight = match weight {
+                Some(weight) => weight.parse::<f64>().map_err(|_| {
+                    Error::invalid(parameter, format!("the weight of {} must be a number, got {:?}", name, weight))
+                })?,
+                None => 1.0,
+            };
+            Ok((color, weight))
+        })
+        .collect()
+}
+
+pub fn format_palette(palette: &[(Color, f64)]) -> String {
+    palette
+        .iter()
+        .map(|(color, weight)| format!("{}:{}", color.name(), weight))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Fill {
     Solid(Color),
-    Hatched,
-    Dotted,
+    /// Parallel lines, see `Canvas::hatch_angle` and `Canvas::hatch_spacing`.
+    Hatched(Color),
+    /// A grid of dots, see `Canvas::dotted_spacing` and `Canvas::dotted_radius`.
+    Dotted(Color),
+}
+
+impl Fill {
+    pub const KINDS: [&'static str; 3] = ["Solid", "Hatched", "Dotted"];
+
+    pub fn color(self) -> Color {
+        match self {
+            Fill::Solid(color) | Fill::Hatched(color) | Fill::Dotted(color) => color,
+        }
+    }
+
+    /// Id of the `<pattern>` the fill is painted with, `None` for solid fills.
+    pub fn pattern_id(self) -> Option<String> {
+        match self {
+            Fill::Solid(_) => None,
+            Fill::Hatched(color) => Some(format!("hatched-{}", color.name())),
+            Fill::Dotted(color) => Some(format!("dotted-{}", color.name())),
+        }
+    }
 }
@@ -1,9 +1,10 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::BufReader;
 use serde_json;
+use crate::error::{Error, Result};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ColorMapping {
     pub black: String,
     pub white: String,
@@ -20,8 +21,8 @@ pub struct ColorMapping {
     pub background: String
 }
 
-impl ColorMapping {
-    pub fn default() -> Self {
+impl Default for ColorMapping {
+    fn default() -> Self {
         ColorMapping {
             black: "black".to_string(),
             white: "white".to_string(),
@@ -38,24 +39,75 @@ impl ColorMapping {
             background: "white".to_string(),
         }
     }
-    pub fn from_json_file(path: &str) -> ColorMapping {
-        let file = File::open(path).unwrap();
-        let reader = BufReader::new(file);
-        let json: serde_json::Value = serde_json::from_reader(reader).unwrap();
-        ColorMapping {
-            black: json["black"].as_str().unwrap().to_string(),
-            white: json["white"].as_str().unwrap().to_string(),
-            red: json["red"].as_str().unwrap().to_string(),
-            green: json["green"].as_str().unwrap().to_string(),
-            blue: json["blue"].as_str().unwrap().to_string(),
-            yellow: json["yellow"].as_str().unwrap().to_string(),
-            orange: json["orange"].as_str().unwrap().to_string(),
-            purple: json["purple"].as_str().unwrap().to_string(),
-            brown: json["brown"].as_str().unwrap().to_string(),
-            cyan: json["cyan"].as_str().unwrap().to_string(),
-            pink: json["pink"].as_str().unwrap().to_string(),
-            gray: json["gray"].as_str().unwrap().to_string(),
-            background: json["background"].as_str().unwrap().to_string(),
+}
+
+impl ColorMapping {
+    /// Names accepted by `ColorMapping::builtin`.
+    pub const BUILTIN: [&'static str; 3] = ["default", "palenight", "snazzy-light"];
+
+    /// The themes that ship with the plugin, the same as `color_themes` in `lib.typ`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "palenight" => Some(ColorMapping {
+                black: "#676E95".to_string(),
+                white: "#ffffff".to_string(),
+                red: "#ff5572".to_string(),
+                green: "#a9c77d".to_string(),
+                blue: "#82AAFF".to_string(),
+                yellow: "#FFCB6B".to_string(),
+                orange: "#FFCB6B".to_string(),
+                purple: "#C792EA".to_string(),
+                brown: "#ff5572".to_string(),
+                pink: "#C792EA".to_string(),
+                gray: "#ffffff".to_string(),
+                cyan: "#89DDFF".to_string(),
+                background: "#eeeeee".to_string(),
+            }),
+            "snazzy-light" => Some(ColorMapping {
+                black: "#565869".to_string(),
+                white: "#FFFFFF".to_string(),
+                red: "#FF5C57".to_string(),
+                green: "#2DAE58".to_string(),
+                blue: "#09A1ED".to_string(),
+                yellow: "#F5B900".to_string(),
+                orange: "#CF9C00".to_string(),
+                purple: "#F767BB".to_string(),
+                brown: "#FFAEAC".to_string(),
+                pink: "#FF94D2".to_string(),
+                gray: "#FAFBF9".to_string(),
+                cyan: "#13BBB7".to_string(),
+                background: "#eeeeee".to_string(),
+            }),
+            _ => None,
         }
     }
-}
\ No newline at end of file
+
+    pub fn from_json_file(path: &str) -> Result<ColorMapping> {
+        let file = File::open(path).map_err(|e| Error::decode(path, e.to_string()))?;
+        let reader = BufReader::new(file);
+        let json: serde_json::Value =
+            serde_json::from_reader(reader).map_err(|e| Error::decode(path, e.to_string()))?;
+        let color = |name: &str| -> Result<String> {
+            json[name]
+                .as_str()
+                .map(|value| value.to_string())
+                .ok_or_else(|| Error::decode(path, format!("`{}` must be a color string", name)))
+        };
+        Ok(ColorMapping {
+            black: color("black")?,
+            white: color("white")?,
+            red: color("red")?,
+            green: color("green")?,
+            blue: color("blue")?,
+            yellow: color("yellow")?,
+            orange: color("orange")?,
+            purple: color("purple")?,
+            brown: color("brown")?,
+            cyan: color("cyan")?,
+            pink: color("pink")?,
+            gray: color("gray")?,
+            background: color("background")?,
+        })
+    }
+}
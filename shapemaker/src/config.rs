@@ -0,0 +1,381 @@
+use crate::canvas::{Canvas, FillProbabilities, ObjectWeights};
+use crate::color::{format_palette, parse_palette};
+use crate::color_mapping::ColorMapping;
+use crate::effects::{Blur, DropShadow, Glow, Grain};
+use crate::error::{Error, Result};
+use crate::generation::GenerationVersion;
+use crate::rng::{seed_from_string, seeded, ShapeRng};
+use crate::layering::Layering;
+use crate::sketch::Sketchy;
+use crate::style::{BlendMode, ObjectStyles, OpacityRanges};
+use crate::symmetry::Symmetry;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Every canvas setting in a single map, as sent by the Typst side (CBOR).
+/// Missing fields keep the value from `Canvas::default_settings`.
+/// Ranges are inclusive `[min, max]` arrays.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub grid_size: Option<(usize, usize)>,
+    pub cell_size: Option<usize>,
+    pub canvas_padding: Option<usize>,
+    #[serde(deserialize_with = "float")]
+    pub line_width: Option<f32>,
+    #[serde(deserialize_with = "float")]
+    pub small_circle_radius: Option<f32>,
+    #[serde(deserialize_with = "float")]
+    pub dot_radius: Option<f32>,
+    #[serde(deserialize_with = "float")]
+    pub empty_shape_stroke: Option<f32>,
+    pub render_grid: Option<bool>,
+    pub objects_count: Option<(usize, usize)>,
+    pub polygon_vertices: Option<(usize, usize)>,
+    pub seed: Option<Seed>,
+    pub theme: Option<Theme>,
+    pub algorithm: Option<GenerationVersion>,
+    pub object_weights: Option<ObjectWeights>,
+    /// Colors to pick from with optional weights, like `blue:3,cyan:2,black`. Empty for every color.
+    pub colors: Option<String>,
+    pub fill_probabilities: Option<FillProbabilities>,
+    #[serde(deserialize_with = "probability")]
+    pub hatched_fill_probability: Option<f64>,
+    #[serde(deserialize_with = "probability")]
+    pub dotted_fill_probability: Option<f64>,
+    #[serde(deserialize_with = "float")]
+    pub hatch_angle: Option<f32>,
+    #[serde(deserialize_with = "float")]
+    pub hatch_spacing: Option<f32>,
+    #[serde(deserialize_with = "float")]
+    pub dotted_spacing: Option<f32>,
+    #[serde(deserialize_with = "float")]
+    pub dotted_radius: Option<f32>,
+    #[serde(deserialize_with = "float")]
+    pub curvature: Option<f32>,
+    pub diagonal_curves_only: Option<bool>,
+    #[serde(deserialize_with = "probability")]
+    pub center_anchor_probability: Option<f64>,
+    pub symmetry: Option<Symmetry>,
+    pub layering: Option<Layering>,
+    pub object_styles: Option<ObjectStyles>,
+    #[serde(deserialize_with = "float")]
+    pub opacity: Option<f32>,
+    pub opacity_ranges: Option<OpacityRanges>,
+    pub blend_mode: Option<BlendMode>,
+    pub drop_shadow: Option<DropShadow>,
+    pub glow: Option<Glow>,
+    pub blur: Option<Blur>,
+    pub grain: Option<Grain>,
+    pub sketchy: Option<Sketchy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Seed {
+    Number(u64),
+    Text(String),
+}
+
+/// Either the name of a built-in theme or a full color mapping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Theme {
+    Named(String),
+    Custom(Box<ColorMapping>),
+}
+
+impl Theme {
+    pub fn colormap(&self) -> Result<ColorMapping> {
+        match self {
+            Theme::Named(name) => ColorMapping::builtin(name).ok_or_else(|| {
+                Error::invalid(
+                    "theme",
+                    format!(
+                        "unknown theme {:?}, the built-in themes are {}",
+                        name,
+                        ColorMapping::BUILTIN.join(", ")
+                    ),
+                )
+            }),
+            Theme::Custom(colormap) => Ok(colormap.as_ref().clone()),
+        }
+    }
+}
+
+impl Config {
+    /// Name and expected type of every setting.
+    pub const PARAMETERS: [(&'static str, &'static str); 36] = [
+        ("grid_size", "[int, int]"),
+        ("cell_size", "int"),
+        ("canvas_padding", "int"),
+        ("line_width", "float"),
+        ("small_circle_radius", "float"),
+        ("dot_radius", "float"),
+        ("empty_shape_stroke", "float"),
+        ("render_grid", "bool"),
+        ("objects_count", "[int, int]"),
+        ("polygon_vertices", "[int, int]"),
+        ("seed", "int | str"),
+        ("theme", "str | dictionary"),
+        ("algorithm", "str"),
+        ("object_weights", "dictionary"),
+        ("colors", "str"),
+        ("fill_probabilities", "dictionary"),
+        ("hatched_fill_probability", "float"),
+        ("dotted_fill_probability", "float"),
+        ("hatch_angle", "float"),
+        ("hatch_spacing", "float"),
+        ("dotted_spacing", "float"),
+        ("dotted_radius", "float"),
+        ("curvature", "float"),
+        ("diagonal_curves_only", "bool"),
+        ("center_anchor_probability", "float | none"),
+        ("symmetry", "str"),
+        ("layering", "str"),
+        ("object_styles", "dictionary"),
+        ("opacity", "float"),
+        ("opacity_ranges", "dictionary"),
+        ("blend_mode", "str"),
+        ("drop_shadow", "dictionary | none"),
+        ("glow", "dictionary | none"),
+        ("blur", "dictionary | none"),
+        ("grain", "dictionary | none"),
+        ("sketchy", "dictionary | none"),
+    ];
+
+    /// Every setting with its value in `Canvas::default_settings`.
+    pub fn defaults() -> Self {
+        Config {
+            theme: Some(Theme::Named("default".to_string())),
+            ..Self::from_canvas(&Canvas::default_settings())
+        }
+    }
+
+    pub fn from_canvas(canvas: &Canvas) -> Self {
+        Config {
+            grid_size: Some(canvas.grid_size),
+            cell_size: Some(canvas.cell_size),
+            canvas_padding: Some(canvas.canvas_outter_padding),
+            line_width: Some(canvas.line_width),
+            small_circle_radius: Some(canvas.small_circle_radius),
+            dot_radius: Some(canvas.dot_radius),
+            empty_shape_stroke: Some(canvas.empty_shape_stroke_width),
+            render_grid: Some(canvas.render_grid),
+            objects_count: Some((
+                canvas.objects_count_range.start,
+                canvas.objects_count_range.end.saturating_sub(1),
+            )),
+            polygon_vertices: Some((
+                canvas.polygon_vertices_range.start,
+                canvas.polygon_vertices_range.end.saturating_sub(1),
+            )),
+            seed: Some(Seed::Text(String::new())),
+            theme: Some(Theme::Custom(Box::new(canvas.colormap.clone()))),
+            algorithm: Some(canvas.algorithm),
+            object_weights: Some(canvas.object_weights.clone()),
+            colors: Some(format_palette(&canvas.palette)),
+            fill_probabilities: Some(canvas.fill_probabilities.clone()),
+            hatched_fill_probability: Some(canvas.hatched_fill_probability),
+            dotted_fill_probability: Some(canvas.dotted_fill_probability),
+            hatch_angle: Some(canvas.hatch_angle),
+            hatch_spacing: Some(canvas.hatch_spacing),
+            dotted_spacing: Some(canvas.dotted_spacing),
+            dotted_radius: Some(canvas.dotted_radius),
+            curvature: Some(canvas.curvature),
+            diagonal_curves_only: Some(canvas.diagonal_curves_only),
+            center_anchor_probability: canvas.center_anchor_probability,
+            symmetry: Some(canvas.symmetry),
+            layering: Some(canvas.layering),
+            object_styles: Some(canvas.object_styles.clone()),
+            opacity: Some(canvas.opacity),
+            opacity_ranges: Some(canvas.opacity_ranges.clone()),
+            blend_mode: Some(canvas.blend_mode),
+            drop_shadow: canvas.drop_shadow.clone(),
+            glow: canvas.glow.clone(),
+            blur: canvas.blur.clone(),
+            grain: canvas.grain.clone(),
+            sketchy: canvas.sketchy.clone(),
+        }
+    }
+
+    pub fn canvas(&self) -> Result<Canvas> {
+        let mut canvas = Canvas::default_settings();
+        self.apply(&mut canvas)?;
+        Ok(canvas)
+    }
+
+    pub fn apply(&self, canvas: &mut Canvas) -> Result<()> {
+        if let Some(grid_size) = self.grid_size {
+            canvas.grid_size = grid_size;
+        }
+        if let Some(cell_size) = self.cell_size {
+            canvas.cell_size = cell_size;
+        }
+        if let Some(canvas_padding) = self.canvas_padding {
+            canvas.canvas_outter_padding = canvas_padding;
+        }
+        if let Some(line_width) = self.line_width {
+            canvas.line_width = line_width;
+        }
+        if let Some(small_circle_radius) = self.small_circle_radius {
+            canvas.small_circle_radius = small_circle_radius;
+        }
+        if let Some(dot_radius) = self.dot_radius {
+            canvas.dot_radius = dot_radius;
+        }
+        if let Some(empty_shape_stroke) = self.empty_shape_stroke {
+            canvas.empty_shape_stroke_width = empty_shape_stroke;
+        }
+        if let Some(render_grid) = self.render_grid {
+            canvas.render_grid = render_grid;
+        }
+        if let Some((min, max)) = self.objects_count {
+            canvas.objects_count_range = min..range_end("objects_count", max)?;
+        }
+        if let Some((min, max)) = self.polygon_vertices {
+            canvas.polygon_vertices_range = min..range_end("polygon_vertices", max)?;
+        }
+        if let Some(theme) = &self.theme {
+            canvas.colormap = theme.colormap()?;
+        }
+        if let Some(algorithm) = self.algorithm {
+            canvas.algorithm = algorithm;
+        }
+        if let Some(object_weights) = &self.object_weights {
+            canvas.object_weights = object_weights.clone();
+        }
+        if let Some(colors) = &self.colors {
+            canvas.palette = parse_palette("colors", colors)?;
+        }
+        if let Some(fill_probabilities) = &self.fill_probabilities {
+            canvas.fill_probabilities = fill_probabilities.clone();
+        }
+        if let Some(hatched_fill_probability) = self.hatched_fill_probability {
+            canvas.hatched_fill_probability = hatched_fill_probability;
+        }
+        if let Some(dotted_fill_probability) = self.dotted_fill_probability {
+            canvas.dotted_fill_probability = dotted_fill_probability;
+        }
+        if let Some(hatch_angle) = self.hatch_angle {
+            canvas.hatch_angle = hatch_angle;
+        }
+        if let Some(hatch_spacing) = self.hatch_spacing {
+            canvas.hatch_spacing = hatch_spacing;
+        }
+        if let Some(dotted_spacing) = self.dotted_spacing {
+            canvas.dotted_spacing = dotted_spacing;
+        }
+        if let Some(dotted_radius) = self.dotted_radius {
+            canvas.dotted_radius = dotted_radius;
+        }
+        if let Some(curvature) = self.curvature {
+            canvas.curvature = curvature;
+        }
+        if let Some(diagonal_curves_only) = self.diagonal_curves_only {
+            canvas.diagonal_curves_only = diagonal_curves_only;
+        }
+        if let Some(center_anchor_probability) = self.center_anchor_probability {
+            canvas.center_anchor_probability = Some(center_anchor_probability);
+        }
+        if let Some(symmetry) = self.symmetry {
+            canvas.symmetry = symmetry;
+        }
+        if let Some(layering) = self.layering {
+            canvas.layering = layering;
+        }
+        if let Some(object_styles) = &self.object_styles {
+            canvas.object_styles = object_styles.clone();
+        }
+        if let Some(opacity) = self.opacity {
+            canvas.opacity = opacity;
+        }
+        if let Some(opacity_ranges) = &self.opacity_ranges {
+            canvas.opacity_ranges = opacity_ranges.clone();
+        }
+        if let Some(blend_mode) = self.blend_mode {
+            canvas.blend_mode = blend_mode;
+        }
+        if let Some(drop_shadow) = &self.drop_shadow {
+            canvas.drop_shadow = Some(drop_shadow.clone());
+        }
+        if let Some(glow) = &self.glow {
+            canvas.glow = Some(glow.clone());
+        }
+        if let Some(blur) = &self.blur {
+            canvas.blur = Some(blur.clone());
+        }
+        if let Some(grain) = &self.grain {
+            canvas.grain = Some(grain.clone());
+        }
+        if let Some(sketchy) = &self.sketchy {
+            canvas.sketchy = Some(sketchy.clone());
+        }
+        Ok(())
+    }
+
+    /// The numeric seed shapes are generated from, no seed behaves like an empty seed string.
+    pub fn seed(&self) -> u64 {
+        match &self.seed {
+            Some(Seed::Number(seed)) => *seed,
+            Some(Seed::Text(seed)) => seed_from_string(seed),
+            None => seed_from_string(""),
+        }
+    }
+
+    pub fn rng(&self) -> ShapeRng {
+        seeded(self.seed())
+    }
+}
+
+/// Exclusive end of the canvas range with that inclusive maximum.
+pub(crate) fn range_end(parameter: &str, max: usize) -> Result<usize> {
+    max.checked_add(1)
+        .ok_or_else(|| Error::invalid(parameter, format!("the maximum must be below {}, got {}", usize::MAX, max)))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl From<Number> for f64 {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Integer(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+}
+
+// Typst encodes `3` as a CBOR integer and `3.0` as a float, accept both.
+pub fn float<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<f32>, D::Error> {
+    Ok(Option::<Number>::deserialize(deserializer)?.map(|number| f64::from(number) as f32))
+}
+
+/// Same as `float`, for lists of numbers.
+pub(crate) fn floats<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<f32>>, D::Error> {
+    Ok(Option::<Vec<Number>>::deserialize(deserializer)?
+        .map(|numbers| numbers.into_iter().map(|number| f64::from(number) as f32).collect()))
+}
+
+/// Same as `float`, for `[min, max]` pairs inside of setting maps.
+pub(crate) fn float_range<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<(f32, f32), D::Error> {
+    let (min, max) = <(Number, Number)>::deserialize(deserializer)?;
+    Ok((f64::from(min) as f32, f64::from(max) as f32))
+}
+
+/// Same as `float`, without losing precision.
+fn probability<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<f64>, D::Error> {
+    Ok(Option::<Number>::deserialize(deserializer)?.map(f64::from))
+}
+
+/// Same as `float`, for numbers inside of setting maps.
+pub(crate) fn number<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<f64, D::Error> {
+    Ok(Number::deserialize(deserializer)?.into())
+}
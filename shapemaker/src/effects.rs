@@ -0,0 +1,342 @@
+use crate::canvas::Canvas;
+use crate::color::{Color, Fill};
+use crate::error::{Error, Result};
+use crate::shape::Object;
+use serde::{Deserialize, Serialize};
+use svg::node::element::{Element, Filter};
+use svg::Node;
+
+/// A shadow of the color of `ColorMapping::black` under filled objects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DropShadow {
+    #[serde(deserialize_with = "crate::config::number")]
+    pub dx: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub dy: f64,
+    /// Standard deviation of the blur of its edges.
+    #[serde(deserialize_with = "crate::config::number")]
+    pub blur: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub opacity: f64,
+}
+
+impl Default for DropShadow {
+    fn default() -> Self {
+        DropShadow {
+            dx: 2.0,
+            dy: 2.0,
+            blur: 2.0,
+            opacity: 0.5,
+        }
+    }
+}
+
+/// A halo of their own color around lines, curves and outlines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Glow {
+    #[serde(deserialize_with = "crate::config::number")]
+    pub radius: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub opacity: f64,
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Glow {
+            radius: 3.0,
+            opacity: 0.8,
+        }
+    }
+}
+
+/// Blurs the objects drawn first, as if they were further away.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Blur {
+    #[serde(deserialize_with = "crate::config::number")]
+    pub radius: f64,
+    /// How many of the bottom objects are blurred.
+    pub layers: usize,
+}
+
+impl Default for Blur {
+    fn default() -> Self {
+        Blur { radius: 2.0, layers: 1 }
+    }
+}
+
+/// Paper-like noise of the background color over the drawing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Grain {
+    /// How visible the grain is, from 0 to 1.
+    #[serde(deserialize_with = "crate::config::number")]
+    pub intensity: f64,
+    /// Size of the grains, in pixels.
+    #[serde(deserialize_with = "crate::config::number")]
+    pub scale: f64,
+    pub target: GrainTarget,
+}
+
+impl Default for Grain {
+    fn default() -> Self {
+        Grain {
+            intensity: 0.3,
+            scale: 1.5,
+            target: GrainTarget::Canvas,
+        }
+    }
+}
+
+/// What the grain covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrainTarget {
+    /// The whole drawing, background included.
+    #[default]
+    Canvas,
+    /// Only the inside of filled objects.
+    FilledObjects,
+}
+
+pub const DROP_SHADOW_ID: &str = "drop-shadow";
+pub const GLOW_ID: &str = "glow";
+pub const BLUR_ID: &str = "blur";
+pub const GRAIN_ID: &str = "grain";
+
+pub(crate) fn validate(canvas: &Canvas) -> Result<()> {
+    let mut checks = vec![];
+    if let Some(shadow) = &canvas.drop_shadow {
+        checks.push(("drop_shadow", shadow.blur, shadow.opacity));
+        if !(shadow.dx.is_finite() && shadow.dy.is_finite()) {
+            return Err(Error::invalid(
+                "drop_shadow",
+                format!("offsets must be numbers, got {} and {}", shadow.dx, shadow.dy),
+            ));
+        }
+    }
+    if let Some(glow) = &canvas.glow {
+        checks.push(("glow", glow.radius, glow.opacity));
+    }
+    if let Some(blur) = &canvas.blur {
+        checks.push(("blur", blur.radius, 1.0));
+    }
+    if let Some(grain) = &canvas.grain {
+        if !(grain.scale.is_finite() && grain.scale > 0.0) {
+            return Err(Error::invalid("grain", format!("scale must be above zero, got {}", grain.scale)));
+        }
+        checks.push(("grain", 0.0, grain.intensity));
+    }
+    for (parameter, radius, opacity) in checks {
+        if !(radius.is_finite() && radius >= 0.0) {
+            return Err(Error::invalid(
+                parameter,
+                format!("radius must be zero or more, got {}", radius),
+            ));
+        }
+        if !(0.0..=1.0).contains(&opacity) {
+            return Err(Error::invalid(
+                parameter,
+                format!("opacity must be between 0 and 1, got {}", opacity),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The `<filter>` of every enabled effect, for the `<defs>` of the drawing.
+/// `render_seed` is the shape's, see `Shape::render_seed`.
+pub(crate) fn filters(canvas: &Canvas, render_seed: u64) -> Vec<Filter> {
+    let mut filters = vec![];
+    if let Some(shadow) = &canvas.drop_shadow {
+        filters.push(
+            region(DROP_SHADOW_ID, canvas)
+                .add(primitive(
+                    "feGaussianBlur",
+                    &[("in", "SourceAlpha".into()), ("stdDeviation", shadow.blur.to_string())],
+                    vec![],
+                ))
+                .add(primitive(
+                    "feOffset",
+                    &[
+                        ("dx", shadow.dx.to_string()),
+                        ("dy", shadow.dy.to_string()),
+                        ("result", "offset".into()),
+                    ],
+                    vec![],
+                ))
+                .add(primitive(
+                    "feFlood",
+                    &[
+                        ("flood-color", Color::Black.to_string(&canvas.colormap)),
+                        ("flood-opacity", shadow.opacity.to_string()),
+                    ],
+                    vec![],
+                ))
+                .add(primitive(
+                    "feComposite",
+                    &[("in2", "offset".into()), ("operator", "in".into())],
+                    vec![],
+                ))
+                .add(merge(&[None, Some("SourceGraphic")])),
+        );
+    }
+    if let Some(glow) = &canvas.glow {
+        filters.push(
+            region(GLOW_ID, canvas)
+                .add(primitive(
+                    "feGaussianBlur",
+                    &[("in", "SourceGraphic".into()), ("stdDeviation", glow.radius.to_string())],
+                    vec![],
+                ))
+                .add(primitive(
+                    "feComponentTransfer",
+                    &[],
+                    vec![primitive(
+                        "feFuncA",
+                        &[("type", "linear".into()), ("slope", glow.opacity.to_string())],
+                        vec![],
+                    )],
+                ))
+                .add(merge(&[None, Some("SourceGraphic")])),
+        );
+    }
+    if let Some(blur) = &canvas.blur {
+        filters.push(region(BLUR_ID, canvas).add(primitive(
+            "feGaussianBlur",
+            &[("stdDeviation", blur.radius.to_string())],
+            vec![],
+        )));
+    }
+    if let Some(grain) = &canvas.grain {
+        let intensity = grain.intensity * 2.0;
+        let mut filter = region(GRAIN_ID, canvas)
+            .add(primitive(
+                "feTurbulence",
+                &[
+                    ("type", "fractalNoise".into()),
+                    ("baseFrequency", (1.0 / grain.scale).to_string()),
+                    ("numOctaves", "3".into()),
+                    // usvg only takes small seeds
+                    ("seed", (render_seed as u16).to_string()),
+                ],
+                vec![],
+            ))
+            // Only the brighter half of the noise shows, so that grains stand apart
+            .add(primitive(
+                "feColorMatrix",
+                &[
+                    ("type", "matrix".into()),
+                    (
+                        "values",
+                        format!("0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 {} 0 0 0 {}", intensity, -grain.intensity),
+                    ),
+                    ("result", "noise".into()),
+                ],
+                vec![],
+            ))
+            .add(primitive("feFlood", &[("flood-color", canvas.colormap.background.clone())], vec![]))
+            .add(primitive(
+                "feComposite",
+                &[("in2", "noise".into()), ("operator", "in".into())],
+                vec![],
+            ));
+        if grain.target == GrainTarget::FilledObjects {
+            filter = filter
+                .add(primitive(
+                    "feComposite",
+                    &[("in2", "SourceAlpha".into()), ("operator", "in".into())],
+                    vec![],
+                ))
+                // The grain goes over the fill
+                .add(merge(&[Some("SourceGraphic"), None]));
+        }
+        filters.push(filter);
+    }
+    filters
+}
+
+/// Ids of the filters the object is drawn with, the innermost first, `layer` being its
+/// place in the drawing order. Blurred layers are only blurred, then filled areas get
+/// the grain and the shadow and strokes the glow.
+pub(crate) fn filter_ids(
+    object: &Object,
+    fill: Option<Fill>,
+    layer: usize,
+    canvas: &Canvas,
+) -> Vec<&'static str> {
+    if canvas.blur.as_ref().is_some_and(|blur| layer < blur.layers) {
+        return vec![BLUR_ID];
+    }
+    let filled = object.fills_area() && fill.is_some();
+    let mut ids = vec![];
+    if filled && canvas.grain.as_ref().is_some_and(|grain| grain.target == GrainTarget::FilledObjects) {
+        ids.push(GRAIN_ID);
+    }
+    if filled && canvas.drop_shadow.is_some() {
+        ids.push(DROP_SHADOW_ID);
+    }
+    if !filled && canvas.glow.is_some() {
+        ids.push(GLOW_ID);
+    }
+    ids
+}
+
+/// The layer of grain over the whole drawing, when the grain covers the canvas.
+pub(crate) fn grain_overlay(canvas: &Canvas) -> Option<svg::node::element::Rectangle> {
+    let grain = canvas.grain.as_ref()?;
+    if grain.target != GrainTarget::Canvas {
+        return None;
+    }
+    let (width, height) = canvas.dimensions();
+    let padding = -(canvas.canvas_outter_padding as i32);
+    Some(
+        svg::node::element::Rectangle::new()
+            .set("x", padding)
+            .set("y", padding)
+            .set("width", width)
+            .set("height", height)
+            .set("fill", canvas.colormap.background.clone())
+            .set("filter", format!("url(#{})", GRAIN_ID)),
+    )
+}
+
+/// Filters cover the whole drawing in user space: with the default bounding box region,
+/// horizontal and vertical lines (zero high or wide boxes) would not be drawn at all.
+fn region(id: &str, canvas: &Canvas) -> Filter {
+    let (width, height) = canvas.dimensions();
+    let padding = -(canvas.canvas_outter_padding as i32);
+    Filter::new()
+        .set("id", id)
+        .set("filterUnits", "userSpaceOnUse")
+        .set("x", padding)
+        .set("y", padding)
+        .set("width", width)
+        .set("height", height)
+}
+
+/// Stacks the inputs, the last one on top, `None` being the result of the previous primitive.
+fn merge(inputs: &[Option<&str>]) -> Element {
+    let nodes = inputs
+        .iter()
+        .map(|input| match input {
+            Some(input) => primitive("feMergeNode", &[("in", input.to_string())], vec![]),
+            None => primitive("feMergeNode", &[], vec![]),
+        })
+        .collect();
+    primitive("feMerge", &[], nodes)
+}
+
+/// A filter primitive, the svg crate only has the `<filter>` element itself.
+fn primitive(name: &str, attributes: &[(&str, String)], children: Vec<Element>) -> Element {
+    let mut element = Element::new(name);
+    for (attribute, value) in attributes {
+        element.assign(*attribute, value.clone());
+    }
+    for child in children {
+        element.append(child);
+    }
+    element
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// A setting has a value that shapes cannot be generated with.
+    InvalidParameter { parameter: String, reason: String },
+    /// An input could not be decoded at all (bad CBOR, bad JSON, missing file...).
+    Decode { input: String, reason: String },
+    /// The shape itself cannot be drawn.
+    Render(String),
+    /// A result could not be encoded for the caller.
+    Encode(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn invalid(parameter: impl Into<String>, reason: impl Into<String>) -> Self {
+        Error::InvalidParameter {
+            parameter: parameter.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn decode(input: impl Into<String>, reason: impl Into<String>) -> Self {
+        Error::Decode {
+            input: input.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidParameter { parameter, reason } => {
+                write!(f, "shapemaker: invalid `{}`: {}", parameter, reason)
+            }
+            Error::Decode { input, reason } => {
+                write!(f, "shapemaker: could not read `{}`: {}", input, reason)
+            }
+            Error::Render(reason) => write!(f, "shapemaker: cannot render shape: {}", reason),
+            Error::Encode(reason) => write!(f, "shapemaker: could not encode result: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,44 @@
+use crate::canvas::Canvas;
+use crate::color::Fill;
+use crate::shape::Object;
+use serde::{Deserialize, Serialize};
+
+/// Order objects are drawn in, the last one ends up on top.
+/// Objects that compare equal keep their generation order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layering {
+    /// The order objects were generated in.
+    #[default]
+    Generation,
+    /// By area then size, the largest first, see `Object::area` and `Object::extent`.
+    LargeToSmall,
+    /// Filled areas first, then outlines, lines and curves.
+    FilledFirst,
+    /// Lines and curves on top of every other object.
+    StrokedOnTop,
+}
+
+impl Layering {
+    /// Indices of the objects in the order they are drawn in.
+    pub fn order(self, objects: &[(Object, Option<Fill>)], canvas: &Canvas) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..objects.len()).collect();
+        match self {
+            Layering::Generation => {}
+            Layering::LargeToSmall => order.sort_by(|a, b| {
+                let (a, b) = (&objects[*a].0, &objects[*b].0);
+                let size = |object: &Object| {
+                    let (width, height) = object.extent(canvas);
+                    (object.area(canvas), width.hypot(height))
+                };
+                size(b).partial_cmp(&size(a)).unwrap_or(std::cmp::Ordering::Equal)
+            }),
+            Layering::FilledFirst => order.sort_by_key(|index| {
+                let (object, fill) = &objects[*index];
+                !(object.fills_area() && fill.is_some())
+            }),
+            Layering::StrokedOnTop => order.sort_by_key(|index| !objects[*index].0.fills_area()),
+        }
+        order
+    }
+}
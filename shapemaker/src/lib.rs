@@ -1,3 +1,6 @@
+use crate::canvas::Canvas;
+use crate::color::parse_palette;
+use crate::error::{Error, Result};
 use serde::Deserialize;
 
 pub mod color_mapping;
@@ -5,9 +8,22 @@ pub mod canvas;
 pub mod shape;
 pub mod color;
 pub mod getrandom_custom;
+pub mod rng;
+pub mod config;
+pub mod error;
+pub mod generation;
+pub mod symmetry;
+pub mod layering;
+pub mod style;
+pub mod effects;
+pub mod sketch;
 
+/// Version of the shapemaker generation this crate is a port of.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[derive(Debug, Deserialize)]
+
+/// Command line flags, see `Args::parse`.
+#[derive(Debug, Default, Deserialize)]
 pub struct Args {
     pub flag_version: bool,
     pub flag_color: Vec<String>,
@@ -23,3 +39,109 @@ pub struct Args {
     pub flag_objects_count: Option<String>,
     pub flag_polygon_vertices: Option<String>,
 }
+
+impl Args {
+    /// Reads `--name value` flags, `--version` and `--render-grid` take no value
+    /// and `--color` can be repeated, e.g. `--grid-size 3x3 --color blue:3 --color cyan`.
+    pub fn parse(arguments: impl IntoIterator<Item = String>) -> Result<Args> {
+        fn number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T> {
+            value
+                .parse()
+                .map_err(|_| Error::invalid(flag, format!("expected a number, got {:?}", value)))
+        }
+
+        let mut args = Args::default();
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            let flag = argument
+                .strip_prefix("--")
+                .ok_or_else(|| Error::invalid(argument.as_str(), "expected a --flag"))?;
+            match flag {
+                "version" => args.flag_version = true,
+                "render-grid" => args.flag_render_grid = true,
+                _ => {
+                    let value = arguments
+                        .next()
+                        .ok_or_else(|| Error::invalid(flag, "missing value"))?;
+                    match flag {
+                        "color" => args.flag_color.push(value),
+                        "colors" => args.flag_colors = Some(value),
+                        "grid-size" => args.flag_grid_size = Some(value),
+                        "cell-size" => args.flag_cell_size = Some(number(flag, &value)?),
+                        "canvas-padding" => args.flag_canvas_padding = Some(number(flag, &value)?),
+                        "line-width" => args.flag_line_width = Some(number(flag, &value)?),
+                        "small-circle-radius" => args.flag_small_circle_radius = Some(number(flag, &value)?),
+                        "dot-radius" => args.flag_dot_radius = Some(number(flag, &value)?),
+                        "empty-shape-stroke" => args.flag_empty_shape_stroke = Some(number(flag, &value)?),
+                        "objects-count" => args.flag_objects_count = Some(value),
+                        "polygon-vertices" => args.flag_polygon_vertices = Some(value),
+                        _ => return Err(Error::invalid(flag, "unknown flag")),
+                    }
+                }
+            }
+        }
+        Ok(args)
+    }
+
+    /// Sets the canvas settings given as flags, the others keep their value.
+    pub fn apply(&self, canvas: &mut Canvas) -> Result<()> {
+        let args = self;
+        fn parse<T: std::str::FromStr>(parameter: &str, value: Option<&str>, expected: &str) -> Result<T> {
+            value
+                .and_then(|v| v.trim().parse::<T>().ok())
+                .ok_or_else(|| Error::invalid(parameter, format!("expected {}", expected)))
+        }
+
+        if let Some(dimensions) = &args.flag_grid_size {
+            let expected = format!("WIDTHxHEIGHT, got {:?}", dimensions);
+            let mut split = dimensions.split('x');
+            let width = parse::<usize>("grid_size", split.next(), &expected)?;
+            let height = parse::<usize>("grid_size", split.next(), &expected)?;
+            canvas.grid_size = (width, height);
+        }
+        if let Some(cell_size) = args.flag_cell_size {
+            canvas.cell_size = cell_size;
+        }
+        if let Some(canvas_padding) = args.flag_canvas_padding {
+            canvas.canvas_outter_padding = canvas_padding;
+        }
+        if let Some(line_width) = args.flag_line_width {
+            canvas.line_width = line_width;
+        }
+        if let Some(small_circle_radius) = args.flag_small_circle_radius {
+            canvas.small_circle_radius = small_circle_radius;
+        }
+        if let Some(dot_radius) = args.flag_dot_radius {
+            canvas.dot_radius = dot_radius;
+        }
+        if let Some(empty_shape_stroke) = args.flag_empty_shape_stroke {
+            canvas.empty_shape_stroke_width = empty_shape_stroke;
+        }
+        if let Some(objects_count) = &args.flag_objects_count {
+            let expected = format!("MIN..MAX, got {:?}", objects_count);
+            let mut split = objects_count.split("..");
+            let min = parse::<usize>("objects_count", split.next(), &expected)?;
+            let max = parse::<usize>("objects_count", split.next(), &expected)?;
+            canvas.objects_count_range = min..config::range_end("objects_count", max)?;
+        }
+        if let Some(polygon_vertices) = &args.flag_polygon_vertices {
+            let expected = format!("MIN..MAX, got {:?}", polygon_vertices);
+            let mut split = polygon_vertices.split("..");
+            let min = parse::<usize>("polygon_vertices", split.next(), &expected)?;
+            let max = parse::<usize>("polygon_vertices", split.next(), &expected)?;
+            canvas.polygon_vertices_range = min..config::range_end("polygon_vertices", max)?;
+        }
+        // --color can be repeated, --colors takes the whole palette, both add up
+        let mut palette = vec![];
+        for color in &args.flag_color {
+            palette.extend(parse_palette("color", color)?);
+        }
+        if let Some(colors) = &args.flag_colors {
+            palette.extend(parse_palette("colors", colors)?);
+        }
+        if !palette.is_empty() {
+            canvas.palette = palette;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The generator shapes are drawn with. ChaCha8 gives the same stream for a given seed
+/// on every platform, unlike `StdRng` or `thread_rng`.
+pub type ShapeRng = ChaCha8Rng;
+
+pub fn seeded(seed: u64) -> ShapeRng {
+    ShapeRng::seed_from_u64(seed)
+}
+
+/// Hashes a seed string, e.g. the one `lib.typ` builds from the document, into a numeric seed.
+/// Strings that only differ by one character get unrelated seeds.
+pub fn seed_from_string(input: &str) -> u64 {
+    let mut state = mix(input.len() as u64);
+    for chunk in input.as_bytes().chunks(8) {
+        let mut word = [0u8; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        state = mix(state ^ u64::from_le_bytes(word));
+    }
+    state
+}
+
+/// Seed for the `index`-th shape generated from `seed`, e.g. in a batch.
+pub fn derive_seed(seed: u64, index: u64) -> u64 {
+    mix(seed ^ mix(index))
+}
+
+/// splitmix64: one step of its counter followed by its finalizer.
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
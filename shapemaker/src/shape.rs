@@ -1,8 +1,15 @@
 use crate::canvas::Canvas;
 use crate::color::*;
+use crate::effects;
+use crate::rng::derive_seed;
+use crate::sketch;
+use crate::error::Result;
+use crate::generation::GenerationVersion;
+use crate::style::Style;
+use serde::{Deserialize, Serialize};
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Object {
     Polygon(Anchor, Vec<Line>),
     Line(Anchor, Anchor),
@@ -10,75 +17,223 @@ pub enum Object {
     CurveInward(Anchor, Anchor),
     SmallCircle(Anchor),
     Dot(Anchor),
-    BigCircle(CenterAnchor),
+    BigCircle(Anchor),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Anchor(pub i32, pub i32);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct CenterAnchor(pub i32, pub i32);
-
-pub trait Coordinates {
-    fn coords(&self, canvas: &Canvas) -> (f32, f32);
-    fn center() -> Self;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Anchor {
+    /// A point of the grid, (0, 0) is the top-left one.
+    Point(i32, i32),
+    /// The center of a cell of the grid, (0, 0) is the top-left one.
+    Cell(i32, i32),
+    /// The center of the canvas, see `coords` for generation versions before 1.4.
+    Center,
 }
 
-impl Coordinates for Anchor {
-    fn coords(&self, canvas: &Canvas) -> (f32, f32) {
-        match self {
-            Anchor(-1, -1) => (canvas.cell_size as f32 / 2.0, canvas.cell_size as f32 / 2.0),
-            Anchor(i, j) => {
+impl Anchor {
+    /// Pixel coordinates of the anchor, (0, 0) is the top-left point of the grid.
+    pub fn coords(&self, canvas: &Canvas) -> (f32, f32) {
+        let cell_size = canvas.cell_size as f32;
+        match *self {
+            Anchor::Point(i, j) => {
                 let x = (i * canvas.cell_size as i32) as f32;
                 let y = (j * canvas.cell_size as i32) as f32;
                 (x, y)
             }
+            Anchor::Cell(i, j) => {
+                let x = i as f32 * cell_size + cell_size / 2.0;
+                let y = j as f32 * cell_size + cell_size / 2.0;
+                (x, y)
+            }
+            // Versions before 1.4 put it at the center of the top-left cell, the canvas center of 2x2 grids only
+            Anchor::Center if canvas.algorithm < GenerationVersion::V1_4 => (cell_size / 2.0, cell_size / 2.0),
+            Anchor::Center => (
+                (canvas.grid_size.0 as f32 - 1.0) * cell_size / 2.0,
+                (canvas.grid_size.1 as f32 - 1.0) * cell_size / 2.0,
+            ),
         }
     }
 
-    fn center() -> Self {
-        Anchor(-1, -1)
+    /// Position on a grid twice as fine, which has every point, cell center and the canvas center on it.
+    pub(crate) fn doubled(&self, canvas: &Canvas) -> (i32, i32) {
+        match *self {
+            Anchor::Point(i, j) => (2 * i, 2 * j),
+            Anchor::Cell(i, j) => (2 * i + 1, 2 * j + 1),
+            Anchor::Center if canvas.algorithm < GenerationVersion::V1_4 => (-2, -2),
+            Anchor::Center => (canvas.grid_size.0 as i32 - 1, canvas.grid_size.1 as i32 - 1),
+        }
     }
 }
 
-impl Coordinates for CenterAnchor {
-    fn coords(&self, canvas: &Canvas) -> (f32, f32) {
-        match self {
-            CenterAnchor(-1, -1) => ((canvas.cell_size / 2) as f32, (canvas.cell_size / 2) as f32),
-            CenterAnchor(i, j) => {
-                let x = *i as f32 * canvas.cell_size as f32 + canvas.cell_size as f32 / 2.0;
-                let y = *j as f32 * canvas.cell_size as f32 + canvas.cell_size as f32 / 2.0;
-                (x, y)
-            }
+/// Versions before 1.4 rounded the center of big circles at the canvas center down to a whole pixel.
+pub(crate) fn big_circle_center(center: Anchor, canvas: &Canvas) -> (f32, f32) {
+    match center {
+        Anchor::Center if canvas.algorithm < GenerationVersion::V1_4 => {
+            ((canvas.cell_size / 2) as f32, (canvas.cell_size / 2) as f32)
         }
-    }
-
-    fn center() -> Self {
-        CenterAnchor(-1, -1)
+        _ => center.coords(canvas),
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Line {
     Line(Anchor),
     InwardCurve(Anchor),
     OutwardCurve(Anchor),
 }
 
+impl Line {
+    pub fn end(&self) -> Anchor {
+        match self {
+            Line::Line(end) | Line::InwardCurve(end) | Line::OutwardCurve(end) => *end,
+        }
+    }
+}
+
+impl Object {
+    /// Every kind of object, as named in serialized shapes.
+    pub const KINDS: [&'static str; 7] = [
+        "Polygon",
+        "Line",
+        "CurveOutward",
+        "CurveInward",
+        "SmallCircle",
+        "Dot",
+        "BigCircle",
+    ];
+
+    /// Pixel coordinates of the object's anchors, in drawing order.
+    /// (0, 0) is the top-left anchor, the image itself starts at `-canvas_outter_padding`.
+    pub fn points(&self, canvas: &Canvas) -> Vec<(f32, f32)> {
+        match self {
+            Object::Polygon(start, lines) => std::iter::once(start.coords(canvas))
+                .chain(lines.iter().map(|line| line.end().coords(canvas)))
+                .collect(),
+            Object::Line(start, end)
+            | Object::CurveInward(start, end)
+            | Object::CurveOutward(start, end) => vec![start.coords(canvas), end.coords(canvas)],
+            Object::SmallCircle(center) | Object::Dot(center) => vec![center.coords(canvas)],
+            Object::BigCircle(center) => vec![big_circle_center(*center, canvas)],
+        }
+    }
+
+    /// Radius of circles and dots, `None` for other objects.
+    pub fn radius(&self, canvas: &Canvas) -> Option<f32> {
+        match self {
+            Object::SmallCircle(_) => Some(canvas.small_circle_radius),
+            Object::Dot(_) => Some(canvas.dot_radius),
+            Object::BigCircle(_) => Some((canvas.cell_size / 2) as f32),
+            _ => None,
+        }
+    }
+
+    /// Area the object covers in square pixels, zero for lines and curves.
+    /// Polygons are measured along straight edges, even the curved ones.
+    pub fn area(&self, canvas: &Canvas) -> f32 {
+        match self {
+            Object::Polygon(..) => {
+                let vertices = self.points(canvas);
+                let doubled: f32 = vertices
+                    .iter()
+                    .zip(vertices.iter().cycle().skip(1))
+                    .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+                    .sum();
+                doubled.abs() / 2.0
+            }
+            Object::Line(..) | Object::CurveInward(..) | Object::CurveOutward(..) => 0.0,
+            _ => std::f32::consts::PI * self.radius(canvas).unwrap_or_default().powi(2),
+        }
+    }
+
+    /// Width and height of the object's bounding box in pixels, curves without their bulge.
+    pub fn extent(&self, canvas: &Canvas) -> (f32, f32) {
+        if let Some(radius) = self.radius(canvas) {
+            return (2.0 * radius, 2.0 * radius);
+        }
+        let points = self.points(canvas);
+        let span = |coordinate: fn(&(f32, f32)) -> f32| {
+            let values = points.iter().map(coordinate);
+            values.clone().fold(f32::MIN, f32::max) - values.fold(f32::MAX, f32::min)
+        };
+        (span(|point| point.0), span(|point| point.1))
+    }
+
+    /// The anchor the object starts from, its center for circles and dots.
+    pub fn first_anchor(&self) -> Anchor {
+        match self {
+            Object::Polygon(start, _)
+            | Object::Line(start, _)
+            | Object::CurveInward(start, _)
+            | Object::CurveOutward(start, _) => *start,
+            Object::SmallCircle(center) | Object::Dot(center) | Object::BigCircle(center) => *center,
+        }
+    }
+
+    /// The same object with every anchor moved by `f`.
+    pub fn map_anchors(&self, f: impl Fn(Anchor) -> Anchor) -> Object {
+        match self {
+            Object::Polygon(start, lines) => Object::Polygon(
+                f(*start),
+                lines
+                    .iter()
+                    .map(|line| match line {
+                        Line::Line(end) => Line::Line(f(*end)),
+                        Line::InwardCurve(end) => Line::InwardCurve(f(*end)),
+                        Line::OutwardCurve(end) => Line::OutwardCurve(f(*end)),
+                    })
+                    .collect(),
+            ),
+            Object::Line(start, end) => Object::Line(f(*start), f(*end)),
+            Object::CurveInward(start, end) => Object::CurveInward(f(*start), f(*end)),
+            Object::CurveOutward(start, end) => Object::CurveOutward(f(*start), f(*end)),
+            Object::SmallCircle(center) => Object::SmallCircle(f(*center)),
+            Object::Dot(center) => Object::Dot(f(*center)),
+            Object::BigCircle(center) => Object::BigCircle(f(*center)),
+        }
+    }
+
+    /// Whether the object's fill paints an area, lines and curves use it as their stroke color instead.
+    pub fn fills_area(&self) -> bool {
+        !matches!(
+            self,
+            Object::Line(..) | Object::CurveInward(..) | Object::CurveOutward(..)
+        )
+    }
+}
+
 
 
 #[derive(Debug)]
 pub struct Shape {
     pub objects: Vec<(Object, Option<Fill>)>,
+    /// Opacity of each object, before the canvas opacity. Objects past its end get
+    /// the lower end of their kind's range, see `Canvas::opacity_ranges`.
+    pub opacities: Vec<f32>,
+    /// Seed of the grain noise and the sketchy jitter, see `Canvas::random_render_seed`.
+    pub render_seed: u64,
 }
 
 impl Shape {
-    pub fn render(self, canvas: &Canvas) -> String {
-        let canvas_width =
-            canvas.cell_size * (canvas.grid_size.0 - 1) + 2 * canvas.canvas_outter_padding;
-        let canvas_height =
-            canvas.cell_size * (canvas.grid_size.1 - 1) + 2 * canvas.canvas_outter_padding;
-        let default_color = Color::Black.to_string(&canvas.colormap);
+    pub fn opacity(&self, index: usize, canvas: &Canvas) -> f32 {
+        match self.opacities.get(index) {
+            Some(opacity) => *opacity,
+            None => canvas.opacity_ranges.of(&self.objects[index].0).0,
+        }
+    }
+
+    /// Whether both shapes have the same objects at the same anchors, fills aside.
+    pub fn same_structure(&self, other: &Shape) -> bool {
+        self.objects.len() == other.objects.len()
+            && self
+                .objects
+                .iter()
+                .zip(&other.objects)
+                .all(|((a, _), (b, _))| a == b)
+    }
+
+    pub fn render(self, canvas: &Canvas) -> Result<String> {
+        canvas.validate()?;
+        let (canvas_width, canvas_height) = canvas.dimensions();
         let background_color = canvas.colormap.background.clone();
         eprintln!("render: background_color({:?})", background_color);
         let mut svg = svg::Document::new().add(
@@ -89,241 +244,135 @@ impl Shape {
                 .set("height", canvas_height)
                 .set("fill", background_color),
         );
-        for (object, maybe_fill) in self.objects {
+        let mut patterned: Vec<Fill> = vec![];
+        for (object, fill) in &self.objects {
+            if let Some(fill @ (Fill::Hatched(_) | Fill::Dotted(_))) = fill {
+                if object.fills_area() && !patterned.contains(fill) {
+                    patterned.push(*fill);
+                }
+            }
+        }
+        let filters = effects::filters(canvas, self.render_seed);
+        if !patterned.is_empty() || !filters.is_empty() {
+            let mut defs = svg::node::element::Definitions::new();
+            for pattern in patterned.into_iter().filter_map(|fill| pattern(fill, canvas)) {
+                defs = defs.add(pattern);
+            }
+            for filter in filters {
+                defs = defs.add(filter);
+            }
+            svg = svg.add(defs);
+        }
+        for (layer, index) in canvas.layering.order(&self.objects, canvas).into_iter().enumerate() {
+            let (object, maybe_fill) = self.objects[index].clone();
+            let style = Style::resolve(&object, maybe_fill, self.opacity(index, canvas), canvas);
             let mut group = svg::node::element::Group::new();
-            match object {
-                Object::Polygon(start, lines) => {
-                    eprintln!("render: polygon({:?}, {:?})", start, lines);
-                    let mut path = svg::node::element::path::Data::new();
-                    path = path.move_to(start.coords(&canvas));
-                    for line in lines {
-                        path = match line {
-                            Line::Line(end) | Line::InwardCurve(end) | Line::OutwardCurve(end) => {
-                                path.line_to(end.coords(&canvas))
-                            }
-                        };
+            let filter_ids = effects::filter_ids(&object, maybe_fill, layer, canvas);
+            if let Some(sketchy) = &canvas.sketchy {
+                let seed = derive_seed(self.render_seed, index as u64);
+                group = sketch::draw(&object, maybe_fill, &style, seed, sketchy, canvas);
+            } else {
+                let style = style.to_string();
+                match object {
+                    Object::Polygon(start, lines) => {
+                        eprintln!("render: polygon({:?}, {:?})", start, lines);
+                        let mut path = svg::node::element::path::Data::new().move_to(start.coords(canvas));
+                        for edge in polygon_edges(start, &lines, canvas) {
+                            path = match edge.control {
+                                Some(control) => path.quadratic_curve_to((control, edge.to)),
+                                None => path.line_to(edge.to),
+                            };
+                        }
+                        path = path.close();
+                        group = group
+                            .add(svg::node::element::Path::new().set("d", path))
+                            .set("style", style);
                     }
-                    path = path.close();
-                    group = group
-                        .add(svg::node::element::Path::new().set("d", path))
-                        .set(
-                            "style",
-                            match maybe_fill {
-                                // TODO
-                                Some(Fill::Solid(color)) => {
-                                    format!("fill: {};", color.to_string(&canvas.colormap))
-                                }
-                                _ => format!(
-                                    "fill: none; stroke: {}; stroke-width: {}px;",
-                                    default_color, canvas.empty_shape_stroke_width
-                                ),
-                            },
+                    Object::Line(start, end) => {
+                        eprintln!("render: line({:?}, {:?})", start, end);
+                        group = group.add(
+                            svg::node::element::Line::new()
+                                .set("x1", start.coords(canvas).0)
+                                .set("y1", start.coords(canvas).1)
+                                .set("x2", end.coords(canvas).0)
+                                .set("y2", end.coords(canvas).1)
+                                .set("style", style),
                         );
-                }
-                Object::Line(start, end) => {
-                    eprintln!("render: line({:?}, {:?})", start, end);
-                    group = group.add(
-                        svg::node::element::Line::new()
-                            .set("x1", start.coords(&canvas).0)
-                            .set("y1", start.coords(&canvas).1)
-                            .set("x2", end.coords(&canvas).0)
-                            .set("y2", end.coords(&canvas).1)
-                            .set(
-                                "style",
-                                match maybe_fill {
-                                    // TODO
-                                    Some(Fill::Solid(color)) => {
-                                        format!(
-                                            "fill: none; stroke: {}; stroke-width: 2px;",
-                                            color.to_string(&canvas.colormap)
-                                        )
-                                    }
-                                    _ => format!(
-                                        "fill: none; stroke: {}; stroke-width: 2px;",
-                                        default_color
-                                    ),
-                                },
-                            ),
-                    );
-                }
-                Object::CurveInward(start, end) | Object::CurveOutward(start, end) => {
-                    let inward = if matches!(object, Object::CurveInward(_, _)) {
-                        eprintln!("render: curve_inward({:?}, {:?})", start, end);
-                        true
-                    } else {
-                        eprintln!("render: curve_outward({:?}, {:?})", start, end);
-                        false
-                    };
-
-                    let (start_x, start_y) = start.coords(&canvas);
-                    let (end_x, end_y) = end.coords(&canvas);
+                    }
+                    Object::CurveInward(start, end) | Object::CurveOutward(start, end) => {
+                        let inward = if matches!(object, Object::CurveInward(_, _)) {
+                            eprintln!("render: curve_inward({:?}, {:?})", start, end);
+                            true
+                        } else {
+                            eprintln!("render: curve_outward({:?}, {:?})", start, end);
+                            false
+                        };
 
-                    let midpoint = ((start_x + end_x) / 2.0, (start_y + end_y) / 2.0);
-                    let start_from_midpoint = (start_x - midpoint.0, start_y - midpoint.1);
-                    let end_from_midpoint = (end_x - midpoint.0, end_y - midpoint.1);
-                    eprintln!("        midpoint: {:?}", midpoint);
-                    eprintln!(
-                        "        from midpoint: {:?} -> {:?}",
-                        start_from_midpoint, end_from_midpoint
-                    );
-                    let control = {
-                        let relative = (end_x - start_x, end_y - start_y);
-                        eprintln!("        relative: {:?}", relative);
-                        // diagonal line is going like this: \
-                        if start_from_midpoint.0 * start_from_midpoint.1 > 0.0
-                            && end_from_midpoint.0 * end_from_midpoint.1 > 0.0
-                        {
-                            eprintln!("        diagonal \\");
-                            if inward {
-                                (
-                                    midpoint.0 + relative.0.abs() / 2.0,
-                                    midpoint.1 - relative.1.abs() / 2.0,
-                                )
-                            } else {
-                                (
-                                    midpoint.0 - relative.0.abs() / 2.0,
-                                    midpoint.1 + relative.1.abs() / 2.0,
+                        let control = curve_control_point(start.coords(canvas), end.coords(canvas), inward, canvas);
+                        eprintln!("        control: {:?}", control);
+                        group = group.add(
+                            svg::node::element::Path::new()
+                                .set(
+                                    "d",
+                                    svg::node::element::path::Data::new()
+                                        .move_to(start.coords(canvas))
+                                        .quadratic_curve_to((control, end.coords(canvas))),
                                 )
-                            }
-                        // diagonal line is going like this: /
-                        } else if start_from_midpoint.0 * start_from_midpoint.1 < 0.0
-                            && end_from_midpoint.0 * end_from_midpoint.1 < 0.0
-                        {
-                            eprintln!("        diagonal /");
-                            if inward {
-                                (
-                                    midpoint.0 - relative.0.abs() / 2.0,
-                                    midpoint.1 - relative.1.abs() / 2.0,
-                                )
-                            } else {
-                                (
-                                    midpoint.0 + relative.0.abs() / 2.0,
-                                    midpoint.1 + relative.1.abs() / 2.0,
-                                )
-                            }
-                        // line is horizontal
-                        } else if start_y == end_y {
-                            eprintln!("        horizontal");
-                            (
-                                midpoint.0,
-                                midpoint.1
-                                    + (if inward { -1.0 } else { 1.0 }) * relative.0.abs() / 2.0,
-                            )
-                        // line is vertical
-                        } else if start_x == end_x {
-                            eprintln!("        vertical");
-                            (
-                                midpoint.0
-                                    + (if inward { -1.0 } else { 1.0 }) * relative.1.abs() / 2.0,
-                                midpoint.1,
-                            )
-                        } else {
-                            unreachable!()
-                        }
-                    };
-                    eprintln!("        control: {:?}", control);
-                    group = group.add(
-                        svg::node::element::Path::new()
-                            .set(
-                                "d",
-                                svg::node::element::path::Data::new()
-                                    .move_to(start.coords(&canvas))
-                                    .quadratic_curve_to((control, end.coords(&canvas))),
-                            )
-                            .set(
-                                "style",
-                                match maybe_fill {
-                                    // TODO
-                                    Some(Fill::Solid(color)) => {
-                                        format!(
-                                            "fill: none; stroke: {}; stroke-width: {}px;",
-                                            color.to_string(&canvas.colormap),
-                                            canvas.line_width
-                                        )
-                                    }
-                                    _ => format!(
-                                        "fill: none; stroke: {}; stroke-width: {}px;",
-                                        default_color, canvas.line_width
-                                    ),
-                                },
-                            ),
-                    );
-                }
-                Object::SmallCircle(center) => {
-                    eprintln!("render: small_circle({:?})", center);
-                    group = group.add(
-                        svg::node::element::Circle::new()
-                            .set("cx", center.coords(&canvas).0)
-                            .set("cy", center.coords(&canvas).1)
-                            .set("r", canvas.small_circle_radius)
-                            .set(
-                                "style",
-                                match maybe_fill {
-                                    // TODO
-                                    Some(Fill::Solid(color)) => {
-                                        format!("fill: {};", color.to_string(&canvas.colormap))
-                                    }
-                                    _ => format!(
-                                        "fill: none; stroke: {}; stroke-width: {}px;",
-                                        default_color, canvas.empty_shape_stroke_width
-                                    ),
-                                },
-                            ),
-                    );
-                }
-                Object::Dot(center) => {
-                    eprintln!("render: dot({:?})", center);
-                    group = group.add(
-                        svg::node::element::Circle::new()
-                            .set("cx", center.coords(&canvas).0)
-                            .set("cy", center.coords(&canvas).1)
-                            .set("r", canvas.dot_radius)
-                            .set(
-                                "style",
-                                match maybe_fill {
-                                    // TODO
-                                    Some(Fill::Solid(color)) => {
-                                        format!("fill: {};", color.to_string(&canvas.colormap))
-                                    }
-                                    _ => format!(
-                                        "fill: none; stroke: {}; stroke-width: {}px;",
-                                        default_color, canvas.empty_shape_stroke_width
-                                    ),
-                                },
-                            ),
-                    );
-                }
-                Object::BigCircle(center) => {
-                    eprintln!("render: big_circle({:?})", center);
-                    group = group.add(
-                        svg::node::element::Circle::new()
-                            .set("cx", center.coords(&canvas).0)
-                            .set("cy", center.coords(&canvas).1)
-                            .set("r", canvas.cell_size / 2)
-                            .set(
-                                "style",
-                                match maybe_fill {
-                                    // TODO
-                                    Some(Fill::Solid(color)) => {
-                                        format!("fill: {};", color.to_string(&canvas.colormap))
-                                    }
-                                    _ => format!(
-                                        "fill: none; stroke: {}; stroke-width: 0.5px;",
-                                        default_color
-                                    ),
-                                },
-                            ),
-                    );
+                                .set("style", style),
+                        );
+                    }
+                    Object::SmallCircle(center) => {
+                        eprintln!("render: small_circle({:?})", center);
+                        group = group.add(
+                            svg::node::element::Circle::new()
+                                .set("cx", center.coords(canvas).0)
+                                .set("cy", center.coords(canvas).1)
+                                .set("r", canvas.small_circle_radius)
+                                .set("style", style.clone()),
+                        );
+                    }
+                    Object::Dot(center) => {
+                        eprintln!("render: dot({:?})", center);
+                        group = group.add(
+                            svg::node::element::Circle::new()
+                                .set("cx", center.coords(canvas).0)
+                                .set("cy", center.coords(canvas).1)
+                                .set("r", canvas.dot_radius)
+                                .set("style", style),
+                        );
+                    }
+                    Object::BigCircle(center) => {
+                        eprintln!("render: big_circle({:?})", center);
+                        group = group.add(
+                            svg::node::element::Circle::new()
+                                .set("cx", big_circle_center(center, canvas).0)
+                                .set("cy", big_circle_center(center, canvas).1)
+                                .set("r", canvas.cell_size / 2)
+                                .set("style", style),
+                        );
+                    }
                 }
             }
             eprintln!("        fill: {:?}", &maybe_fill);
+            // Every other filter goes on a group around the previous one
+            for (depth, id) in filter_ids.into_iter().enumerate() {
+                let filter = format!("url(#{})", id);
+                group = if depth == 0 {
+                    group.set("filter", filter)
+                } else {
+                    svg::node::element::Group::new().set("filter", filter).add(group)
+                };
+            }
             svg = svg.add(group);
         }
+        if let Some(overlay) = effects::grain_overlay(canvas) {
+            svg = svg.add(overlay);
+        }
         // render a dotted grid
         if canvas.render_grid {
             for i in 0..canvas.grid_size.0 as i32 {
                 for j in 0..canvas.grid_size.1 as i32 {
-                    let (x, y) = Anchor(i, j).coords(&canvas);
+                    let (x, y) = Anchor::Point(i, j).coords(canvas);
                     svg = svg.add(
                         svg::node::element::Circle::new()
                             .set("cx", x)
@@ -333,7 +382,7 @@ impl Shape {
                     );
 
                     // if i < canvas.grid_size.0 as i32 - 1 && j < canvas.grid_size.1 as i32 - 1 {
-                    //     let (x, y) = CenterAnchor(i, j).coords(&canvas);
+                    //     let (x, y) = Anchor::Cell(i, j).coords(canvas);
                     //     svg = svg.add(
                     //         svg::node::element::Circle::new()
                     //             .set("cx", x)
@@ -345,7 +394,7 @@ impl Shape {
                 }
             }
         }
-        svg.set(
+        Ok(svg.set(
             "viewBox",
             format!(
                 "{0} {0} {1} {2}",
@@ -356,6 +405,111 @@ impl Shape {
         )
         .set("width", canvas_width)
         .set("height", canvas_height)
-        .to_string()
+        .to_string())
     }
 }
+
+/// Side of the control point of an outward curve from `start` to `end`, see `control_point`.
+/// Outward curves bulge down, or right when vertical, inward ones the other way.
+pub(crate) fn curve_side(start: (f32, f32), end: (f32, f32)) -> f32 {
+    if end.0 != start.0 {
+        (end.0 - start.0).signum()
+    } else {
+        -(end.1 - start.1).signum()
+    }
+}
+
+/// Control point of the quadratic curve of a `CurveInward` or `CurveOutward` object.
+pub(crate) fn curve_control_point(start: (f32, f32), end: (f32, f32), inward: bool, canvas: &Canvas) -> (f32, f32) {
+    let side = curve_side(start, end);
+    control_point(start, end, if inward { -side } else { side } * canvas.curvature)
+}
+
+/// Edge of a polygon, from one vertex to the next.
+pub(crate) struct Edge {
+    pub from: (f32, f32),
+    /// The control point of the quadratic curve of curved edges.
+    pub control: Option<(f32, f32)>,
+    pub to: (f32, f32),
+}
+
+/// Edges of a polygon, without the one closing it back to `start`.
+pub(crate) fn polygon_edges(start: Anchor, lines: &[Line], canvas: &Canvas) -> Vec<Edge> {
+    let centroid = {
+        let count = lines.len() as f32 + 1.0;
+        let (x, y) = std::iter::once(start)
+            .chain(lines.iter().map(Line::end))
+            .map(|vertex| vertex.coords(canvas))
+            .fold((0.0, 0.0), |(x, y), vertex| (x + vertex.0, y + vertex.1));
+        (x / count, y / count)
+    };
+    let mut edges = vec![];
+    let mut previous = start.coords(canvas);
+    for line in lines {
+        let end = line.end().coords(canvas);
+        let control = match line {
+            Line::InwardCurve(_) | Line::OutwardCurve(_) if canvas.algorithm >= GenerationVersion::V1_3 => {
+                // Bulge towards the centroid for inward curves, away from it otherwise
+                let midpoint = ((previous.0 + end.0) / 2.0, (previous.1 + end.1) / 2.0);
+                let towards_centroid = -(end.1 - previous.1) * (centroid.0 - midpoint.0)
+                    + (end.0 - previous.0) * (centroid.1 - midpoint.1);
+                let side = if towards_centroid < 0.0 { -1.0 } else { 1.0 };
+                let inward = matches!(line, Line::InwardCurve(_));
+                Some(control_point(previous, end, if inward { side } else { -side } * canvas.curvature))
+            }
+            _ => None,
+        };
+        edges.push(Edge {
+            from: previous,
+            control,
+            to: end,
+        });
+        previous = end;
+    }
+    edges
+}
+
+/// Control point of a quadratic curve from `start` to `end`, `bulge` times the length of
+/// the segment away from its midpoint, on the side of (-dy, dx) when positive.
+fn control_point(start: (f32, f32), end: (f32, f32), bulge: f32) -> (f32, f32) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let midpoint = ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0);
+    (midpoint.0 - bulge * dy, midpoint.1 + bulge * dx)
+}
+
+/// The `<pattern>` a hatched or dotted fill refers to, `None` for solid fills.
+fn pattern(fill: Fill, canvas: &Canvas) -> Option<svg::node::element::Pattern> {
+    let color = fill.color().to_string(&canvas.colormap);
+    let pattern = svg::node::element::Pattern::new()
+        .set("id", fill.pattern_id()?)
+        .set("patternUnits", "userSpaceOnUse");
+    Some(match fill {
+        Fill::Solid(_) => return None,
+        Fill::Hatched(_) => {
+            let spacing = canvas.hatch_spacing;
+            pattern
+                .set("width", spacing)
+                .set("height", spacing)
+                .set("patternTransform", format!("rotate({})", canvas.hatch_angle))
+                .add(
+                    svg::node::element::Line::new()
+                        .set("x1", 0)
+                        .set("y1", spacing / 2.0)
+                        .set("x2", spacing)
+                        .set("y2", spacing / 2.0)
+                        .set("stroke", color)
+                        .set("stroke-width", spacing / 4.0),
+                )
+        }
+        Fill::Dotted(_) => {
+            let spacing = canvas.dotted_spacing;
+            pattern.set("width", spacing).set("height", spacing).add(
+                svg::node::element::Circle::new()
+                    .set("cx", spacing / 2.0)
+                    .set("cy", spacing / 2.0)
+                    .set("r", canvas.dotted_radius)
+                    .set("fill", color),
+            )
+        }
+    })
+}
@@ -0,0 +1,333 @@
+use crate::canvas::Canvas;
+use crate::color::Fill;
+use crate::error::{Error, Result};
+use crate::rng::seeded;
+use crate::shape::{big_circle_center, curve_control_point, polygon_edges, Edge, Object};
+use crate::style::Style;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use svg::node::element::path::Data;
+use svg::node::element::{Group, Path};
+
+type Point = (f32, f32);
+
+/// Most hachures in one fill, gaps get wider past it.
+pub const MAX_HACHURES: usize = 500;
+/// Most points around one circle, however big it is.
+pub const MAX_CIRCLE_STEPS: f32 = 360.0;
+
+/// Hand-drawn look, like rough.js: every stroke is drawn twice with some jitter,
+/// circles wobble and solid fills become hachures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sketchy {
+    /// How far strokes stray from the shape, 0 draws them exactly.
+    #[serde(deserialize_with = "crate::config::number")]
+    pub roughness: f64,
+    /// How much lines bend in their middle.
+    #[serde(deserialize_with = "crate::config::number")]
+    pub bowing: f64,
+    /// Angle of the hachures in degrees, 0 is horizontal.
+    #[serde(deserialize_with = "crate::config::number")]
+    pub hachure_angle: f64,
+    /// Distance between two hachures.
+    #[serde(deserialize_with = "crate::config::number")]
+    pub hachure_gap: f64,
+    #[serde(deserialize_with = "crate::config::number")]
+    pub hachure_width: f64,
+}
+
+impl Default for Sketchy {
+    fn default() -> Self {
+        Sketchy {
+            roughness: 1.0,
+            bowing: 1.0,
+            hachure_angle: -41.0,
+            hachure_gap: 4.0,
+            hachure_width: 1.0,
+        }
+    }
+}
+
+pub(crate) fn validate(canvas: &Canvas) -> Result<()> {
+    let Some(sketchy) = &canvas.sketchy else {
+        return Ok(());
+    };
+    if !(sketchy.roughness.is_finite() && sketchy.roughness >= 0.0) {
+        return Err(Error::invalid(
+            "sketchy",
+            format!("roughness must be zero or more, got {}", sketchy.roughness),
+        ));
+    }
+    if !(sketchy.bowing.is_finite() && sketchy.hachure_angle.is_finite()) {
+        return Err(Error::invalid(
+            "sketchy",
+            format!(
+                "bowing and hachure angle must be numbers, got {} and {}",
+                sketchy.bowing, sketchy.hachure_angle
+            ),
+        ));
+    }
+    for (name, value) in [("hachure gap", sketchy.hachure_gap), ("hachure width", sketchy.hachure_width)] {
+        if !(value.is_finite() && value > 0.0) {
+            return Err(Error::invalid("sketchy", format!("{} must be above zero, got {}", name, value)));
+        }
+    }
+    Ok(())
+}
+
+/// The object drawn by hand, its jitter coming from `seed`. The group has the style of
+/// the outline, fills are drawn under it.
+pub(crate) fn draw(
+    object: &Object,
+    fill: Option<Fill>,
+    style: &Style,
+    seed: u64,
+    sketchy: &Sketchy,
+    canvas: &Canvas,
+) -> Group {
+    let mut rng = seeded(seed);
+    let outline_style = Style {
+        fill: "none".to_string(),
+        stroke: Some(style.stroke.clone().unwrap_or_else(|| style.fill.clone())),
+        ..style.clone()
+    };
+    let mut group = Group::new().set("style", outline_style.to_string());
+
+    if object.fills_area() {
+        let outline = outline(object, canvas);
+        let (width, height) = object.extent(canvas);
+        let hachured = width.min(height) >= 2.0 * sketchy.hachure_gap as f32;
+        match fill {
+            Some(Fill::Solid(_)) if hachured => {
+                let mut data = Data::new();
+                for (from, to) in hachures(&outline, sketchy) {
+                    data = line(&mut rng, data, from, to, sketchy, false);
+                }
+                group = group.add(Path::new().set("d", data).set(
+                    "style",
+                    format!(
+                        "fill: none; stroke: {}; stroke-width: {}px; stroke-dasharray: none;",
+                        style.fill, sketchy.hachure_width
+                    ),
+                ));
+            }
+            // Too small for hachures, or patterned: filled as is, under the outline
+            Some(_) => {
+                group = group.add(
+                    Path::new()
+                        .set("d", polyline(&outline).close())
+                        .set("style", format!("fill: {}; stroke: none;", style.fill)),
+                );
+            }
+            None => {}
+        }
+    }
+
+    let mut data = Data::new();
+    for overlay in [false, true] {
+        data = match object {
+            Object::Polygon(start, lines) => {
+                let mut edges = polygon_edges(*start, lines, canvas);
+                if let Some(last) = edges.last() {
+                    edges.push(Edge {
+                        from: last.to,
+                        control: None,
+                        to: start.coords(canvas),
+                    });
+                }
+                edges.iter().fold(data, |data, edge| match edge.control {
+                    Some(control) => curve(&mut rng, data, edge.from, control, edge.to, sketchy, overlay),
+                    None => line(&mut rng, data, edge.from, edge.to, sketchy, overlay),
+                })
+            }
+            Object::Line(start, end) => {
+                line(&mut rng, data, start.coords(canvas), end.coords(canvas), sketchy, overlay)
+            }
+            Object::CurveInward(start, end) | Object::CurveOutward(start, end) => {
+                let (start, end) = (start.coords(canvas), end.coords(canvas));
+                let inward = matches!(object, Object::CurveInward(..));
+                let control = curve_control_point(start, end, inward, canvas);
+                curve(&mut rng, data, start, control, end, sketchy, overlay)
+            }
+            Object::SmallCircle(center) => {
+                circle(&mut rng, data, center.coords(canvas), canvas.small_circle_radius, sketchy)
+            }
+            Object::Dot(center) => circle(&mut rng, data, center.coords(canvas), canvas.dot_radius, sketchy),
+            Object::BigCircle(center) => circle(
+                &mut rng,
+                data,
+                big_circle_center(*center, canvas),
+                (canvas.cell_size / 2) as f32,
+                sketchy,
+            ),
+        };
+    }
+    group.add(Path::new().set("d", data))
+}
+
+/// A random offset of at most `max` either way, scaled by the roughness.
+fn jitter(rng: &mut impl Rng, max: f32, sketchy: &Sketchy) -> f32 {
+    sketchy.roughness as f32 * max * rng.gen_range(-1.0..=1.0)
+}
+
+fn jittered(rng: &mut impl Rng, (x, y): Point, max: f32, sketchy: &Sketchy) -> Point {
+    (x + jitter(rng, max, sketchy), y + jitter(rng, max, sketchy))
+}
+
+/// How far the ends of a stroke of that length may stray, the overlaid stroke strays half as far.
+fn max_offset(length: f32, overlay: bool) -> f32 {
+    let offset = if length < 20.0 { length / 10.0 } else { 2.0 };
+    if overlay {
+        offset / 2.0
+    } else {
+        offset
+    }
+}
+
+/// A straight stroke as a slightly bowed cubic curve, rough.js' way.
+fn line(rng: &mut impl Rng, data: Data, from: Point, to: Point, sketchy: &Sketchy, overlay: bool) -> Data {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let offset = max_offset(dx.hypot(dy), overlay);
+    let bowing = sketchy.bowing as f32 * offset / 100.0;
+    let middle = (jitter(rng, bowing * dy, sketchy), jitter(rng, -bowing * dx, sketchy));
+    let diverge = rng.gen_range(0.2..0.4);
+    let first = (
+        from.0 + middle.0 + dx * diverge,
+        from.1 + middle.1 + dy * diverge,
+    );
+    let second = (
+        from.0 + middle.0 + 2.0 * dx * diverge,
+        from.1 + middle.1 + 2.0 * dy * diverge,
+    );
+    data.move_to(jittered(rng, from, offset, sketchy)).cubic_curve_to((
+        jittered(rng, first, offset, sketchy),
+        jittered(rng, second, offset, sketchy),
+        jittered(rng, to, offset, sketchy),
+    ))
+}
+
+fn curve(
+    rng: &mut impl Rng,
+    data: Data,
+    from: Point,
+    control: Point,
+    to: Point,
+    sketchy: &Sketchy,
+    overlay: bool,
+) -> Data {
+    let offset = max_offset((to.0 - from.0).hypot(to.1 - from.1), overlay);
+    data.move_to(jittered(rng, from, offset, sketchy)).quadratic_curve_to((
+        jittered(rng, control, 2.0 * offset, sketchy),
+        jittered(rng, to, offset, sketchy),
+    ))
+}
+
+/// A wobbly circle that does not quite close, through jittered points around it.
+fn circle(rng: &mut impl Rng, data: Data, center: Point, radius: f32, sketchy: &Sketchy) -> Data {
+    let steps = (radius * 0.9).ceil().clamp(9.0, MAX_CIRCLE_STEPS) as usize;
+    let increment = 2.0 * PI / steps as f32;
+    let offset = max_offset(radius, false).min(radius / 3.0);
+    let start = rng.gen_range(0.0..2.0 * PI);
+    let overlap = increment * rng.gen_range(0.1..0.4);
+    // One point before the start and after the end, for the tangents of the curve
+    let points: Vec<Point> = (0..steps + 3)
+        .map(|step| {
+            let angle = start + (step as f32 - 1.0) * increment + overlap * (step as f32 / steps as f32);
+            let radius = radius + jitter(rng, offset, sketchy);
+            (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+        })
+        .collect();
+    through(data, &points)
+}
+
+/// Catmull-Rom curve through every point but the first and last ones.
+fn through(data: Data, points: &[Point]) -> Data {
+    let mut data = data.move_to(points[1]);
+    for window in points.windows(4) {
+        let [before, from, to, after] = [window[0], window[1], window[2], window[3]];
+        data = data.cubic_curve_to((
+            (from.0 + (to.0 - before.0) / 6.0, from.1 + (to.1 - before.1) / 6.0),
+            (to.0 - (after.0 - from.0) / 6.0, to.1 - (after.1 - from.1) / 6.0),
+            to,
+        ));
+    }
+    data
+}
+
+/// Vertices of a polygon close to the outline of an area object.
+fn outline(object: &Object, canvas: &Canvas) -> Vec<Point> {
+    let around = |center: Point, radius: f32| -> Vec<Point> {
+        (0..32)
+            .map(|step| {
+                let angle = step as f32 * PI / 16.0;
+                (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+            })
+            .collect()
+    };
+    match object {
+        Object::Polygon(start, lines) => {
+            let mut points = vec![start.coords(canvas)];
+            for edge in polygon_edges(*start, lines, canvas) {
+                match edge.control {
+                    Some(control) => points.extend((1..=8).map(|step| {
+                        let t = step as f32 / 8.0;
+                        let along = |from: f32, control: f32, to: f32| {
+                            (1.0 - t) * (1.0 - t) * from + 2.0 * (1.0 - t) * t * control + t * t * to
+                        };
+                        (
+                            along(edge.from.0, control.0, edge.to.0),
+                            along(edge.from.1, control.1, edge.to.1),
+                        )
+                    })),
+                    None => points.push(edge.to),
+                }
+            }
+            points
+        }
+        Object::SmallCircle(center) => around(center.coords(canvas), canvas.small_circle_radius),
+        Object::Dot(center) => around(center.coords(canvas), canvas.dot_radius),
+        Object::BigCircle(center) => around(big_circle_center(*center, canvas), (canvas.cell_size / 2) as f32),
+        Object::Line(..) | Object::CurveInward(..) | Object::CurveOutward(..) => vec![],
+    }
+}
+
+fn polyline(points: &[Point]) -> Data {
+    let mut data = Data::new();
+    for (index, point) in points.iter().enumerate() {
+        data = if index == 0 { data.move_to(*point) } else { data.line_to(*point) };
+    }
+    data
+}
+
+/// Parallel segments filling the polygon, `hachure_gap` apart.
+fn hachures(polygon: &[Point], sketchy: &Sketchy) -> Vec<(Point, Point)> {
+    let angle = (sketchy.hachure_angle as f32).to_radians();
+    let rotate = |(x, y): Point, angle: f32| (x * angle.cos() - y * angle.sin(), x * angle.sin() + y * angle.cos());
+    // Hachures are horizontal in the rotated polygon
+    let rotated: Vec<Point> = polygon.iter().map(|point| rotate(*point, -angle)).collect();
+    let (top, bottom) = rotated
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(top, bottom), (_, y)| (top.min(*y), bottom.max(*y)));
+    let gap = (sketchy.hachure_gap as f32).max((bottom - top) / MAX_HACHURES as f32);
+
+    let mut segments = vec![];
+    for line in 0..MAX_HACHURES {
+        let y = top + gap / 2.0 + line as f32 * gap;
+        if y >= bottom {
+            break;
+        }
+        let mut crossings: Vec<f32> = (0..rotated.len())
+            .filter_map(|index| {
+                let (from, to) = (rotated[index], rotated[(index + 1) % rotated.len()]);
+                ((from.1 <= y) != (to.1 <= y)).then(|| from.0 + (y - from.1) * (to.0 - from.0) / (to.1 - from.1))
+            })
+            .collect();
+        crossings.sort_by(f32::total_cmp);
+        for pair in crossings.chunks_exact(2) {
+            segments.push((rotate((pair[0], y), angle), rotate((pair[1], y), angle)));
+        }
+    }
+    segments
+}
@@ -0,0 +1,286 @@
+use crate::canvas::Canvas;
+use crate::color::{Color, Fill};
+use crate::generation::GenerationVersion;
+use crate::shape::Object;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// How an object mixes with what is drawn under it, like CSS' `mix-blend-mode`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlendMode {
+    /// Drawn over what is under it.
+    #[default]
+    Normal,
+    /// Darkens like overprinted inks.
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+/// Replaces parts of the style a kind of object is drawn with, unset ones keep the default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StyleOverrides {
+    /// Outline color, filled areas get an outline with it too.
+    pub stroke: Option<Color>,
+    #[serde(deserialize_with = "crate::config::float")]
+    pub stroke_width: Option<f32>,
+    pub linecap: Option<LineCap>,
+    pub linejoin: Option<LineJoin>,
+    /// Lengths of dashes and gaps, in pixels.
+    #[serde(deserialize_with = "crate::config::floats")]
+    pub dasharray: Option<Vec<f32>>,
+    pub blend_mode: Option<BlendMode>,
+}
+
+/// Inclusive range each kind of object draws its opacity from, from 0 (invisible) to 1 (opaque).
+/// Kinds with a single value always get it, without drawing anything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpacityRanges {
+    #[serde(deserialize_with = "crate::config::float_range")]
+    pub polygon: (f32, f32),
+    #[serde(deserialize_with = "crate::config::float_range")]
+    pub big_circle: (f32, f32),
+    #[serde(deserialize_with = "crate::config::float_range")]
+    pub small_circle: (f32, f32),
+    #[serde(deserialize_with = "crate::config::float_range")]
+    pub dot: (f32, f32),
+    #[serde(deserialize_with = "crate::config::float_range")]
+    pub curve_inward: (f32, f32),
+    #[serde(deserialize_with = "crate::config::float_range")]
+    pub curve_outward: (f32, f32),
+    #[serde(deserialize_with = "crate::config::float_range")]
+    pub line: (f32, f32),
+}
+
+impl Default for OpacityRanges {
+    fn default() -> Self {
+        OpacityRanges {
+            polygon: (1.0, 1.0),
+            big_circle: (1.0, 1.0),
+            small_circle: (1.0, 1.0),
+            dot: (1.0, 1.0),
+            curve_inward: (1.0, 1.0),
+            curve_outward: (1.0, 1.0),
+            line: (1.0, 1.0),
+        }
+    }
+}
+
+impl OpacityRanges {
+    pub fn of(&self, object: &Object) -> (f32, f32) {
+        match object {
+            Object::Polygon(..) => self.polygon,
+            Object::BigCircle(..) => self.big_circle,
+            Object::SmallCircle(..) => self.small_circle,
+            Object::Dot(..) => self.dot,
+            Object::CurveInward(..) => self.curve_inward,
+            Object::CurveOutward(..) => self.curve_outward,
+            Object::Line(..) => self.line,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> [(f32, f32); 7] {
+        [
+            self.polygon,
+            self.big_circle,
+            self.small_circle,
+            self.dot,
+            self.curve_inward,
+            self.curve_outward,
+            self.line,
+        ]
+    }
+}
+
+/// Style overrides for each kind of object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectStyles {
+    pub polygon: StyleOverrides,
+    pub big_circle: StyleOverrides,
+    pub small_circle: StyleOverrides,
+    pub dot: StyleOverrides,
+    pub curve_inward: StyleOverrides,
+    pub curve_outward: StyleOverrides,
+    pub line: StyleOverrides,
+}
+
+impl ObjectStyles {
+    pub fn of(&self, object: &Object) -> &StyleOverrides {
+        match object {
+            Object::Polygon(..) => &self.polygon,
+            Object::BigCircle(..) => &self.big_circle,
+            Object::SmallCircle(..) => &self.small_circle,
+            Object::Dot(..) => &self.dot,
+            Object::CurveInward(..) => &self.curve_inward,
+            Object::CurveOutward(..) => &self.curve_outward,
+            Object::Line(..) => &self.line,
+        }
+    }
+}
+
+/// How an object is painted, written out as its `style` attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    /// CSS paint of the inside, `none` for lines, curves and empty shapes.
+    pub fill: String,
+    /// CSS color of the outline, `None` for solid areas which have none.
+    pub stroke: Option<String>,
+    pub stroke_width: f32,
+    pub linecap: Option<LineCap>,
+    pub linejoin: Option<LineJoin>,
+    pub dasharray: Vec<f32>,
+    /// Already multiplied by the canvas opacity.
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+}
+
+impl Style {
+    /// The canvas defaults for the object, with the overrides of its kind on top.
+    /// `opacity` is the object's own, see `Shape::opacities`.
+    pub fn resolve(object: &Object, fill: Option<Fill>, opacity: f32, canvas: &Canvas) -> Style {
+        let colormap = &canvas.colormap;
+        // Versions before 1.5 drew lines 2px wide and big circle outlines 0.5px wide, whatever the settings
+        let legacy = canvas.algorithm < GenerationVersion::V1_5;
+        let (fill, stroke, stroke_width) = if object.fills_area() {
+            match fill {
+                Some(Fill::Solid(color)) => (color.to_string(colormap), None, canvas.empty_shape_stroke_width),
+                Some(fill) => (
+                    format!("url(#{})", fill.pattern_id().unwrap_or_default()),
+                    Some(fill.color().to_string(colormap)),
+                    canvas.empty_shape_stroke_width,
+                ),
+                None => (
+                    "none".to_string(),
+                    Some(Color::Black.to_string(colormap)),
+                    match object {
+                        Object::BigCircle(..) if legacy => 0.5,
+                        _ => canvas.empty_shape_stroke_width,
+                    },
+                ),
+            }
+        } else {
+            (
+                "none".to_string(),
+                Some(fill.map_or(Color::Black, Fill::color).to_string(colormap)),
+                match object {
+                    Object::Line(..) if legacy => 2.0,
+                    _ => canvas.line_width,
+                },
+            )
+        };
+
+        let overrides = canvas.object_styles.of(object);
+        Style {
+            fill,
+            stroke: overrides.stroke.map(|color| color.to_string(colormap)).or(stroke),
+            stroke_width: overrides.stroke_width.unwrap_or(stroke_width),
+            linecap: overrides.linecap,
+            linejoin: overrides.linejoin,
+            dasharray: overrides.dasharray.clone().unwrap_or_default(),
+            opacity: canvas.opacity * opacity,
+            blend_mode: overrides.blend_mode.unwrap_or(canvas.blend_mode),
+        }
+    }
+}
+
+impl fmt::Display for LineCap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        })
+    }
+}
+
+impl fmt::Display for LineJoin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        })
+    }
+}
+
+impl fmt::Display for BlendMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BlendMode::Normal => "normal",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen => "screen",
+            BlendMode::Overlay => "overlay",
+            BlendMode::Darken => "darken",
+            BlendMode::Lighten => "lighten",
+            BlendMode::ColorDodge => "color-dodge",
+            BlendMode::ColorBurn => "color-burn",
+            BlendMode::HardLight => "hard-light",
+            BlendMode::SoftLight => "soft-light",
+            BlendMode::Difference => "difference",
+            BlendMode::Exclusion => "exclusion",
+            BlendMode::Hue => "hue",
+            BlendMode::Saturation => "saturation",
+            BlendMode::Color => "color",
+            BlendMode::Luminosity => "luminosity",
+        })
+    }
+}
+
+/// The CSS of the `style` attribute.
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fill: {};", self.fill)?;
+        if let Some(stroke) = &self.stroke {
+            write!(f, " stroke: {}; stroke-width: {}px;", stroke, self.stroke_width)?;
+        }
+        if let Some(linecap) = self.linecap {
+            write!(f, " stroke-linecap: {};", linecap)?;
+        }
+        if let Some(linejoin) = self.linejoin {
+            write!(f, " stroke-linejoin: {};", linejoin)?;
+        }
+        if !self.dasharray.is_empty() {
+            let lengths: Vec<String> = self.dasharray.iter().map(f32::to_string).collect();
+            write!(f, " stroke-dasharray: {};", lengths.join(" "))?;
+        }
+        if self.opacity < 1.0 {
+            write!(f, " opacity: {};", self.opacity)?;
+        }
+        if self.blend_mode != BlendMode::Normal {
+            write!(f, " mix-blend-mode: {};", self.blend_mode)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use crate::canvas::Canvas;
+use crate::color::Fill;
+use crate::generation::GenerationVersion;
+use crate::shape::{curve_side, Anchor, Object, Shape};
+use serde::{Deserialize, Serialize};
+
+/// Symmetry of generated shapes. Objects are drawn in the fundamental region of the
+/// symmetry, then copied by every other transformation of it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// The left half mirrored onto the right half.
+    Horizontal,
+    /// The top half mirrored onto the bottom half.
+    Vertical,
+    /// Mirrored both ways, from the top-left quarter.
+    Both,
+    /// Unchanged by a half turn.
+    Rotational2,
+    /// Unchanged by a quarter turn, needs a square grid.
+    Rotational4,
+}
+
+/// Mirrors then turns anchors within the grid.
+#[derive(Clone, Copy)]
+struct Transform {
+    mirror_x: bool,
+    mirror_y: bool,
+    quarter_turns: u8,
+}
+
+impl Transform {
+    const IDENTITY: Transform = Transform::new(false, false, 0);
+
+    const fn new(mirror_x: bool, mirror_y: bool, quarter_turns: u8) -> Self {
+        Transform {
+            mirror_x,
+            mirror_y,
+            quarter_turns,
+        }
+    }
+
+    /// Whether the transformation flips the plane over.
+    fn mirror(self) -> bool {
+        self.mirror_x != self.mirror_y
+    }
+
+    fn apply(self, anchor: Anchor, canvas: &Canvas) -> Anchor {
+        let (width, height) = canvas.grid_size;
+        // There is one less cell than points in each direction
+        let (cell, (mut i, mut j), (last_i, last_j)) = match anchor {
+            // Versions before 1.4 draw it at the center of the top-left cell, see `Anchor::coords`
+            Anchor::Center if canvas.algorithm < GenerationVersion::V1_4 => {
+                (true, (0, 0), (width as i32 - 2, height as i32 - 2))
+            }
+            Anchor::Center => return Anchor::Center,
+            Anchor::Point(i, j) => (false, (i, j), (width as i32 - 1, height as i32 - 1)),
+            Anchor::Cell(i, j) => (true, (i, j), (width as i32 - 2, height as i32 - 2)),
+        };
+        if self.mirror_x {
+            i = last_i - i;
+        }
+        if self.mirror_y {
+            j = last_j - j;
+        }
+        for _ in 0..self.quarter_turns {
+            // Clockwise, only on square grids
+            (i, j) = (last_j - j, i);
+        }
+        if cell {
+            Anchor::Cell(i, j)
+        } else {
+            Anchor::Point(i, j)
+        }
+    }
+}
+
+impl Symmetry {
+    fn transforms(self) -> Vec<Transform> {
+        let mut transforms = vec![Transform::IDENTITY];
+        transforms.extend(match self {
+            Symmetry::None => vec![],
+            Symmetry::Horizontal => vec![Transform::new(true, false, 0)],
+            Symmetry::Vertical => vec![Transform::new(false, true, 0)],
+            Symmetry::Both => vec![
+                Transform::new(true, false, 0),
+                Transform::new(false, true, 0),
+                Transform::new(true, true, 0),
+            ],
+            Symmetry::Rotational2 => vec![Transform::new(true, true, 0)],
+            Symmetry::Rotational4 => vec![
+                Transform::new(false, false, 1),
+                Transform::new(false, false, 2),
+                Transform::new(false, false, 3),
+            ],
+        });
+        transforms
+    }
+
+    /// Whether the anchor is in the part of the grid objects are drawn in.
+    fn in_fundamental_region(self, anchor: Anchor, canvas: &Canvas) -> bool {
+        if anchor == Anchor::Center {
+            return true;
+        }
+        // Twice the offset from the center of the grid, so that cells are on whole numbers too
+        let (x, y) = anchor.doubled(canvas);
+        let (x, y) = (
+            x - (canvas.grid_size.0 as i32 - 1),
+            y - (canvas.grid_size.1 as i32 - 1),
+        );
+        match self {
+            Symmetry::None => true,
+            Symmetry::Horizontal => x <= 0,
+            Symmetry::Vertical => y <= 0,
+            Symmetry::Both => x <= 0 && y <= 0,
+            Symmetry::Rotational2 => y < 0 || y == 0 && x <= 0,
+            Symmetry::Rotational4 => x < 0 && y <= 0 || x == 0 && y == 0,
+        }
+    }
+
+    /// Moves every object of the shape into the fundamental region and adds its copies after
+    /// all of them, so that objects keep their index. Copies on top of another object are left out.
+    /// Copies have the fill and opacity of their original.
+    pub fn apply(self, shape: Shape, canvas: &Canvas) -> Shape {
+        if self == Symmetry::None {
+            return shape;
+        }
+        let transforms = self.transforms();
+        let mut opacities: Vec<f32> = (0..shape.objects.len())
+            .map(|index| shape.opacity(index, canvas))
+            .collect();
+        let originals: Vec<(Object, Option<Fill>)> = shape
+            .objects
+            .into_iter()
+            .map(|(object, fill)| {
+                let into_region = transforms
+                    .iter()
+                    .copied()
+                    .find(|transform| {
+                        let first = transform.apply(object.first_anchor(), canvas);
+                        self.in_fundamental_region(first, canvas)
+                    })
+                    .unwrap_or(Transform::IDENTITY);
+                (transformed(&object, into_region, canvas), fill)
+            })
+            .collect();
+
+        let mut objects = originals.clone();
+        for transform in &transforms[1..] {
+            for (index, (object, fill)) in originals.iter().enumerate() {
+                let copy = transformed(object, *transform, canvas);
+                if !objects.iter().any(|(other, _)| same_place(&copy, other)) {
+                    objects.push((copy, *fill));
+                    opacities.push(opacities[index]);
+                }
+            }
+        }
+        Shape {
+            objects,
+            opacities,
+            render_seed: shape.render_seed,
+        }
+    }
+}
+
+fn transformed(object: &Object, transform: Transform, canvas: &Canvas) -> Object {
+    let moved = object.map_anchors(|anchor| transform.apply(anchor, canvas));
+    // Mirroring moves the control point of curves to the other side of the segment,
+    // which can be the other sense for the direction the moved curve goes in.
+    // Polygon edges bulge relative to the centroid, which moves along with them.
+    match (object, moved) {
+        (
+            Object::CurveInward(start, end) | Object::CurveOutward(start, end),
+            Object::CurveInward(new_start, new_end) | Object::CurveOutward(new_start, new_end),
+        ) => {
+            let inward = matches!(object, Object::CurveInward(..));
+            let side = curve_side(start.coords(canvas), end.coords(canvas));
+            let mut bulge = if inward { -side } else { side };
+            if transform.mirror() {
+                bulge = -bulge;
+            }
+            if bulge == curve_side(new_start.coords(canvas), new_end.coords(canvas)) {
+                Object::CurveOutward(new_start, new_end)
+            } else {
+                Object::CurveInward(new_start, new_end)
+            }
+        }
+        (_, moved) => moved,
+    }
+}
+
+/// Whether both objects draw the same thing, lines and curves can go either way.
+fn same_place(a: &Object, b: &Object) -> bool {
+    match (a, b) {
+        (Object::Line(a1, a2), Object::Line(b1, b2))
+        | (Object::CurveInward(a1, a2), Object::CurveInward(b1, b2))
+        | (Object::CurveOutward(a1, a2), Object::CurveOutward(b1, b2)) => {
+            (a1, a2) == (b1, b2) || (a1, a2) == (b2, b1)
+        }
+        _ => a == b,
+    }
+}
@@ -0,0 +1,42 @@
+use shapemaker::canvas::{Canvas, ObjectWeights, MAX_BATCH_COUNT};
+use shapemaker::error::Error;
+
+#[test]
+fn batches_are_seeded_by_position() {
+    let canvas = Canvas::default_settings();
+    let shapes = canvas.random_batch(7, 5, true).unwrap();
+    assert_eq!(shapes.len(), 5);
+    for (shape, first) in canvas.random_batch(7, 3, true).unwrap().iter().zip(&shapes) {
+        assert!(shape.same_structure(first) && shape.render_seed == first.render_seed);
+    }
+}
+
+#[test]
+fn batch_counts_are_capped() {
+    let canvas = Canvas::default_settings();
+    for (count, unique) in [(MAX_BATCH_COUNT + 1, false), (usize::MAX, true), (usize::MAX / 2, true)] {
+        match canvas.random_batch(0, count, unique) {
+            Err(Error::InvalidParameter { parameter, .. }) => assert_eq!(parameter, "count"),
+            other => panic!("{} shapes: {:?}", count, other.map(|shapes| shapes.len())),
+        }
+    }
+    // A single dot has few places to go
+    let tiny = Canvas {
+        grid_size: (2, 2),
+        objects_count_range: 1..2,
+        object_weights: ObjectWeights {
+            polygon: 0.0,
+            big_circle: 0.0,
+            small_circle: 0.0,
+            curve_inward: 0.0,
+            curve_outward: 0.0,
+            line: 0.0,
+            dot: 1.0,
+        },
+        ..Canvas::default_settings()
+    };
+    assert!(matches!(
+        tiny.random_batch(0, 20, true),
+        Err(Error::InvalidParameter { ref parameter, .. }) if parameter == "unique"
+    ));
+}
@@ -0,0 +1,25 @@
+use shapemaker::config::Config;
+use shapemaker::error::Error;
+
+#[test]
+fn ranges_up_to_the_largest_number_are_rejected() {
+    for config in [
+        Config {
+            objects_count: Some((1, usize::MAX)),
+            ..Config::default()
+        },
+        Config {
+            polygon_vertices: Some((3, usize::MAX)),
+            ..Config::default()
+        },
+    ] {
+        assert!(matches!(config.canvas(), Err(Error::InvalidParameter { .. })));
+    }
+    let canvas = Config {
+        objects_count: Some((2, 5)),
+        ..Config::default()
+    }
+    .canvas()
+    .unwrap();
+    assert_eq!(canvas.objects_count_range, 2..6);
+}
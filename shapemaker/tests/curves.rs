@@ -0,0 +1,61 @@
+use shapemaker::canvas::{Canvas, ObjectWeights};
+use shapemaker::rng::seeded;
+use shapemaker::shape::{Anchor, Object, Shape};
+
+fn only_curves() -> Canvas {
+    Canvas {
+        object_weights: ObjectWeights {
+            polygon: 0.0,
+            big_circle: 0.0,
+            small_circle: 0.0,
+            dot: 0.0,
+            line: 0.0,
+            ..ObjectWeights::default()
+        },
+        ..Canvas::default_settings()
+    }
+}
+
+#[test]
+fn curves_can_end_on_any_anchor() {
+    let canvas = Canvas {
+        diagonal_curves_only: false,
+        ..only_curves()
+    };
+    let mut straight = 0;
+    for seed in 0..100 {
+        let shape = canvas.random_shape(&mut seeded(seed)).unwrap();
+        for (object, _) in &shape.objects {
+            if let Object::CurveInward(start, end) | Object::CurveOutward(start, end) = object {
+                assert_ne!(start, end);
+                if let (Anchor::Point(x1, y1), Anchor::Point(x2, y2)) = (start, end) {
+                    if x1 == x2 || y1 == y2 {
+                        straight += 1;
+                    }
+                }
+            }
+        }
+        shape.render(&canvas).unwrap();
+    }
+    assert!(straight > 0);
+}
+
+#[test]
+fn curvature_moves_the_control_point() {
+    let render = |curvature| {
+        let canvas = Canvas {
+            curvature,
+            ..only_curves()
+        };
+        Shape {
+            objects: vec![(Object::CurveOutward(Anchor::Point(0, 0), Anchor::Point(2, 0)), None)],
+            opacities: vec![],
+            render_seed: 0,
+        }
+        .render(&canvas)
+        .unwrap()
+    };
+    assert!(render(0.5).contains("M0,0 Q50,50,100,0"), "{}", render(0.5));
+    assert!(render(0.0).contains("M0,0 Q50,0,100,0"), "{}", render(0.0));
+    assert!(render(-0.25).contains("M0,0 Q50,-25,100,0"), "{}", render(-0.25));
+}
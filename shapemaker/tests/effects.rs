@@ -0,0 +1,116 @@
+use shapemaker::canvas::Canvas;
+use shapemaker::color::{Color, Fill};
+use shapemaker::effects::{Blur, DropShadow, Glow, Grain, GrainTarget};
+use shapemaker::generation::GenerationVersion;
+use shapemaker::rng::seeded;
+use shapemaker::shape::{Anchor, Object, Shape};
+
+fn shape() -> Shape {
+    Shape {
+        objects: vec![
+            (Object::BigCircle(Anchor::Cell(0, 0)), Some(Fill::Solid(Color::Red))),
+            (Object::Dot(Anchor::Point(1, 1)), Some(Fill::Solid(Color::Blue))),
+            (Object::Line(Anchor::Point(0, 0), Anchor::Point(2, 0)), None),
+        ],
+        opacities: vec![],
+        render_seed: 0,
+    }
+}
+
+#[test]
+fn no_filter_is_defined_by_default() {
+    let svg = shape().render(&Canvas::default_settings()).unwrap();
+    assert!(!svg.contains("<filter") && !svg.contains("filter="), "{}", svg);
+}
+
+#[test]
+fn filters_are_defined_once_and_referenced_by_groups() {
+    let canvas = Canvas {
+        drop_shadow: Some(DropShadow::default()),
+        glow: Some(Glow::default()),
+        ..Canvas::default_settings()
+    };
+    let svg = shape().render(&canvas).unwrap();
+    assert_eq!(svg.matches(r#"<filter filterUnits="userSpaceOnUse""#).count(), 2, "{}", svg);
+    assert_eq!(svg.matches(r#"<g filter="url(#drop-shadow)""#).count(), 2, "{}", svg);
+    assert_eq!(svg.matches(r#"<g filter="url(#glow)""#).count(), 1, "{}", svg);
+    let black = Color::Black.to_string(&canvas.colormap);
+    assert!(svg.contains(&format!(r#"flood-color="{}""#, black)), "{}", svg);
+}
+
+#[test]
+fn blurred_layers_only_get_the_blur() {
+    let canvas = Canvas {
+        drop_shadow: Some(DropShadow::default()),
+        blur: Some(Blur { radius: 4.0, layers: 1 }),
+        ..Canvas::default_settings()
+    };
+    let svg = shape().render(&canvas).unwrap();
+    let groups: Vec<&str> = svg.match_indices("<g").map(|(i, _)| &svg[i..i + 30]).collect();
+    assert!(groups[0].contains("url(#blur)"), "{:?}", groups);
+    assert!(groups[1].contains("url(#drop-shadow)"), "{:?}", groups);
+    assert!(!groups[2].contains("filter"), "{:?}", groups);
+
+    let canvas = Canvas {
+        glow: Some(Glow { radius: -1.0, opacity: 0.5 }),
+        ..Canvas::default_settings()
+    };
+    assert!(canvas.validate().is_err());
+}
+
+#[test]
+fn grain_covers_the_canvas_in_the_background_color() {
+    let canvas = Canvas {
+        grain: Some(Grain::default()),
+        ..Canvas::default_settings()
+    };
+    let svg = Shape { render_seed: 42, ..shape() }.render(&canvas).unwrap();
+    assert!(svg.contains(r#"seed="42""#), "{}", svg);
+    let background = format!(r#"flood-color="{}""#, canvas.colormap.background);
+    assert!(svg.contains(&background), "{}", svg);
+    // The overlay is drawn last, over every object
+    let overlay = svg.rfind("<rect").unwrap();
+    assert!(svg[overlay..].contains("url(#grain)") && !svg[overlay..].contains("<g"), "{}", svg);
+
+    let canvas = Canvas {
+        grain: Some(Grain {
+            target: GrainTarget::FilledObjects,
+            ..Grain::default()
+        }),
+        drop_shadow: Some(DropShadow::default()),
+        ..Canvas::default_settings()
+    };
+    let svg = shape().render(&canvas).unwrap();
+    assert_eq!(svg.matches("<g filter=\"url(#drop-shadow)\">\n<g filter=\"url(#grain)\"").count(), 2, "{}", svg);
+    assert_eq!(svg.matches("<rect").count(), 1, "{}", svg);
+    // The grain is merged over the fill, the shadow under it
+    let grain = &svg[svg.find(r#"id="grain""#).unwrap()..];
+    let grain = &grain[..grain.find("</filter>").unwrap()];
+    assert!(grain.contains("<feMergeNode in=\"SourceGraphic\"/>\n<feMergeNode/>"), "{}", grain);
+    let shadow = &svg[svg.find(r#"id="drop-shadow""#).unwrap()..];
+    let shadow = &shadow[..shadow.find("</filter>").unwrap()];
+    assert!(shadow.contains("<feMergeNode/>\n<feMergeNode in=\"SourceGraphic\"/>"), "{}", shadow);
+}
+
+#[test]
+fn render_seeds_come_from_the_shape_seed_without_changing_the_shape() {
+    for algorithm in GenerationVersion::ALL {
+        let plain = Canvas {
+            algorithm,
+            ..Canvas::default_settings()
+        };
+        let grained = Canvas {
+            grain: Some(Grain::default()),
+            ..plain.clone()
+        };
+        let mut seeds = vec![];
+        for seed in 0..20 {
+            let shape = grained.random_shape(&mut seeded(seed)).unwrap();
+            assert_eq!(shape.objects, plain.random_shape(&mut seeded(seed)).unwrap().objects);
+            assert_eq!(shape.render_seed, grained.random_shape(&mut seeded(seed)).unwrap().render_seed);
+            seeds.push(shape.render_seed);
+        }
+        seeds.dedup();
+        assert!(seeds.len() > 1, "{:?}", seeds);
+    }
+}
@@ -0,0 +1,86 @@
+use shapemaker::canvas::{Canvas, FillProbabilities};
+use shapemaker::color::Fill;
+use shapemaker::rng::seeded;
+use shapemaker::shape::Object;
+
+#[test]
+fn fill_probabilities_apply_per_kind() {
+    let canvas = Canvas {
+        fill_probabilities: FillProbabilities {
+            dot: 1.0,
+            line: 1.0,
+            big_circle: 0.0,
+            ..FillProbabilities::default()
+        },
+        ..Canvas::default_settings()
+    };
+    for seed in 0..200 {
+        for (object, fill) in canvas.random_shape(&mut seeded(seed)).unwrap().objects {
+            match object {
+                Object::Dot(..) | Object::Line(..) => assert!(fill.is_some()),
+                Object::BigCircle(..) => assert!(fill.is_none()),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn fill_probabilities_outside_of_zero_to_one_are_rejected() {
+    let canvas = Canvas {
+        fill_probabilities: FillProbabilities {
+            polygon: 1.5,
+            ..FillProbabilities::default()
+        },
+        ..Canvas::default_settings()
+    };
+    assert!(canvas.random_shape(&mut seeded(0)).is_err());
+}
+
+#[test]
+fn pattern_settings_are_checked() {
+    for canvas in [
+        Canvas { dotted_radius: 0.0, ..Canvas::default_settings() },
+        Canvas { dotted_radius: -1.0, ..Canvas::default_settings() },
+        Canvas { dotted_radius: f32::INFINITY, ..Canvas::default_settings() },
+        Canvas { hatch_angle: f32::NAN, ..Canvas::default_settings() },
+        Canvas { hatch_angle: f32::INFINITY, ..Canvas::default_settings() },
+    ] {
+        assert!(canvas.validate().is_err());
+    }
+    assert!(Canvas { hatch_angle: -30.0, ..Canvas::default_settings() }.validate().is_ok());
+}
+
+#[test]
+fn patterned_fills_are_defined_once_and_referenced() {
+    let canvas = Canvas {
+        hatched_fill_probability: 0.5,
+        dotted_fill_probability: 0.5,
+        fill_probabilities: FillProbabilities {
+            polygon: 1.0,
+            big_circle: 1.0,
+            small_circle: 1.0,
+            dot: 1.0,
+            curve_inward: 1.0,
+            curve_outward: 1.0,
+            line: 1.0,
+        },
+        ..Canvas::default_settings()
+    };
+    for seed in 0..50 {
+        let shape = canvas.random_shape(&mut seeded(seed)).unwrap();
+        let mut ids = vec![];
+        for (object, fill) in &shape.objects {
+            let fill = fill.unwrap_or_else(|| panic!("{:?} is not filled", object));
+            assert!(matches!(fill, Fill::Hatched(_) | Fill::Dotted(_)), "{:?}", fill);
+            if object.fills_area() {
+                ids.push(fill.pattern_id().unwrap());
+            }
+        }
+        let svg = shape.render(&canvas).unwrap();
+        for id in ids {
+            assert_eq!(svg.matches(&format!("id=\"{}\"", id)).count(), 1, "{}", svg);
+            assert!(svg.contains(&format!("fill: url(#{});", id)), "{}", svg);
+        }
+    }
+}
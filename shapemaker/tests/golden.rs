@@ -0,0 +1,55 @@
+// Pins the SVG every generation version draws for a fixed set of seeds, so that
+// documents rebuilt years later get the exact same images.
+// Run with SHAPEMAKER_UPDATE_GOLDEN=1 to write the files of a new version.
+
+use shapemaker::canvas::Canvas;
+use shapemaker::generation::GenerationVersion;
+use shapemaker::rng::seeded;
+use std::path::PathBuf;
+
+const SEEDS: std::ops::Range<u64> = 0..24;
+
+fn render(version: GenerationVersion, seed: u64) -> String {
+    let canvas = Canvas {
+        algorithm: version,
+        ..Canvas::default_settings()
+    };
+    match canvas
+        .random_shape(&mut seeded(seed))
+        .and_then(|shape| shape.render(&canvas))
+    {
+        Ok(svg) => svg,
+        Err(error) => format!("error: {}", error),
+    }
+}
+
+fn golden_file(version: GenerationVersion, seed: u64) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("v{}", version.name().replace('.', "_")))
+        .join(format!("seed-{}.svg", seed))
+}
+
+#[test]
+fn every_version_draws_its_golden_shapes() {
+    let update = std::env::var_os("SHAPEMAKER_UPDATE_GOLDEN").is_some();
+    for version in GenerationVersion::ALL {
+        for seed in SEEDS {
+            let path = golden_file(version, seed);
+            let rendered = render(version, seed);
+            if update && !path.exists() {
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, &rendered).unwrap();
+            }
+            let golden = std::fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("missing golden file {}: {}", path.display(), e));
+            assert!(
+                rendered == golden,
+                "version {} draws something else for seed {} than {}",
+                version.name(),
+                seed,
+                path.display()
+            );
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// Every grid size from 1x1 to 10x10 must generate and render, whatever the settings.
+// Everything is seeded, so these never flake.
+
+use shapemaker::canvas::{Canvas, ObjectWeights};
+use shapemaker::generation::GenerationVersion;
+use shapemaker::rng::seeded;
+use shapemaker::shape::{Anchor, Object};
+
+const SEEDS: std::ops::Range<u64> = 0..20;
+
+fn grids() -> impl Iterator<Item = (usize, usize)> {
+    (1..=10).flat_map(|width| (1..=10).map(move |height| (width, height)))
+}
+
+fn in_grid(anchor: Anchor, (width, height): (usize, usize)) -> bool {
+    match anchor {
+        Anchor::Point(i, j) => (0..width as i32).contains(&i) && (0..height as i32).contains(&j),
+        Anchor::Cell(..) => false,
+        Anchor::Center => true,
+    }
+}
+
+fn in_cells(center: Anchor, (width, height): (usize, usize)) -> bool {
+    match center {
+        Anchor::Cell(i, j) => (0..width as i32 - 1).contains(&i) && (0..height as i32 - 1).contains(&j),
+        Anchor::Point(..) => false,
+        Anchor::Center => true,
+    }
+}
+
+fn assert_placed(object: &Object, grid: (usize, usize)) {
+    let placed = match object {
+        Object::Polygon(start, lines) => {
+            in_grid(*start, grid) && lines.iter().all(|line| in_grid(line.end(), grid))
+        }
+        Object::Line(start, end) => in_grid(*start, grid) && in_grid(*end, grid),
+        Object::CurveInward(start, end) | Object::CurveOutward(start, end) => {
+            start != end && in_grid(*start, grid) && in_grid(*end, grid)
+        }
+        Object::SmallCircle(center) | Object::Dot(center) => in_grid(*center, grid),
+        Object::BigCircle(center) => in_cells(*center, grid),
+    };
+    assert!(placed, "{:?} does not fit on a {}x{} grid", object, grid.0, grid.1);
+}
+
+#[test]
+fn every_small_grid_generates() {
+    for grid_size in grids() {
+        for algorithm in GenerationVersion::ALL {
+            for diagonal_curves_only in [true, false] {
+                let canvas = Canvas {
+                    grid_size,
+                    algorithm,
+                    diagonal_curves_only,
+                    ..Canvas::default_settings()
+                };
+                for seed in SEEDS {
+                    let shape = canvas.random_shape(&mut seeded(seed)).unwrap();
+                    for (object, _) in &shape.objects {
+                        assert_placed(object, grid_size);
+                    }
+                    shape.render(&canvas).unwrap();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn unplaceable_kinds_fall_back() {
+    let only = |big_circle, curve| ObjectWeights {
+        polygon: 0.0,
+        big_circle,
+        small_circle: 0.0,
+        dot: 0.0,
+        curve_inward: curve,
+        curve_outward: curve,
+        line: 0.0,
+    };
+    for grid_size in grids() {
+        for object_weights in [only(1.0, 0.0), only(0.0, 1.0)] {
+            let canvas = Canvas {
+                grid_size,
+                object_weights,
+                ..Canvas::default_settings()
+            };
+            for seed in SEEDS {
+                for (object, _) in canvas.random_shape(&mut seeded(seed)).unwrap().objects {
+                    assert_placed(&object, grid_size);
+                    if let Object::SmallCircle(_) = object {
+                        assert!(grid_size.0 == 1 || grid_size.1 == 1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_grids_are_rejected() {
+    for grid_size in [(0, 0), (0, 3), (3, 0)] {
+        let canvas = Canvas {
+            grid_size,
+            ..Canvas::default_settings()
+        };
+        assert!(canvas.random_shape(&mut seeded(0)).is_err());
+        canvas.random_anchor(&mut seeded(0));
+    }
+}
+
+#[test]
+fn center_anchor_is_the_canvas_center() {
+    let canvas = |algorithm| Canvas {
+        grid_size: (4, 3),
+        algorithm,
+        ..Canvas::default_settings()
+    };
+    assert_eq!(Anchor::Center.coords(&canvas(GenerationVersion::V1_4)), (75.0, 50.0));
+    assert_eq!(Anchor::Center.coords(&canvas(GenerationVersion::V1_3)), (25.0, 25.0));
+
+    let always_center = Canvas {
+        center_anchor_probability: Some(1.0),
+        ..canvas(GenerationVersion::V1_4)
+    };
+    let mut rng = seeded(0);
+    for _ in 0..20 {
+        assert_eq!(always_center.random_anchor(&mut rng), Anchor::Center);
+        assert_eq!(always_center.random_cell_anchor(&mut rng), Some(Anchor::Center));
+    }
+}
@@ -0,0 +1,76 @@
+use shapemaker::canvas::Canvas;
+use shapemaker::color::{Color, Fill};
+use shapemaker::layering::Layering;
+use shapemaker::shape::{Anchor, Line, Object};
+
+fn objects() -> Vec<(Object, Option<Fill>)> {
+    vec![
+        (Object::Dot(Anchor::Point(0, 0)), Some(Fill::Solid(Color::Red))),
+        (Object::Line(Anchor::Point(0, 0), Anchor::Point(2, 2)), None),
+        (
+            Object::Polygon(
+                Anchor::Point(0, 0),
+                vec![
+                    Line::Line(Anchor::Point(2, 0)),
+                    Line::OutwardCurve(Anchor::Point(2, 2)),
+                    Line::Line(Anchor::Point(0, 2)),
+                ],
+            ),
+            Some(Fill::Solid(Color::Blue)),
+        ),
+        (Object::SmallCircle(Anchor::Point(1, 1)), None),
+        (Object::BigCircle(Anchor::Cell(0, 0)), Some(Fill::Solid(Color::Green))),
+    ]
+}
+
+fn kinds(layering: Layering) -> Vec<&'static str> {
+    let objects = objects();
+    layering
+        .order(&objects, &Canvas::default_settings())
+        .into_iter()
+        .map(|index| match objects[index].0 {
+            Object::Polygon(..) => "polygon",
+            Object::Line(..) => "line",
+            Object::SmallCircle(..) => "small circle",
+            Object::Dot(..) => "dot",
+            Object::BigCircle(..) => "big circle",
+            Object::CurveInward(..) | Object::CurveOutward(..) => "curve",
+        })
+        .collect()
+}
+
+#[test]
+fn objects_have_an_area_and_an_extent() {
+    let canvas = Canvas::default_settings();
+    let objects: Vec<Object> = objects().into_iter().map(|(object, _)| object).collect();
+    let [dot, line, square, small_circle, big_circle] = &objects[..] else {
+        unreachable!()
+    };
+    assert_eq!(square.area(&canvas), 10000.0);
+    assert_eq!(square.extent(&canvas), (100.0, 100.0));
+    assert_eq!(line.area(&canvas), 0.0);
+    assert_eq!(line.extent(&canvas), (100.0, 100.0));
+    assert_eq!(big_circle.extent(&canvas), (50.0, 50.0));
+    assert!(big_circle.area(&canvas) > small_circle.area(&canvas));
+    assert!(small_circle.area(&canvas) > dot.area(&canvas));
+}
+
+#[test]
+fn layerings_order_objects() {
+    assert_eq!(
+        kinds(Layering::Generation),
+        ["dot", "line", "polygon", "small circle", "big circle"]
+    );
+    assert_eq!(
+        kinds(Layering::LargeToSmall),
+        ["polygon", "big circle", "small circle", "dot", "line"]
+    );
+    assert_eq!(
+        kinds(Layering::FilledFirst),
+        ["dot", "polygon", "big circle", "line", "small circle"]
+    );
+    assert_eq!(
+        kinds(Layering::StrokedOnTop),
+        ["dot", "polygon", "small circle", "big circle", "line"]
+    );
+}
@@ -0,0 +1,93 @@
+use shapemaker::color::{parse_palette, Color, Fill};
+use shapemaker::canvas::Canvas;
+use shapemaker::config::Config;
+use shapemaker::rng::seeded;
+use shapemaker::Args;
+
+#[test]
+fn shapes_only_use_palette_colors() {
+    let canvas = Config {
+        colors: Some("blue,cyan".to_string()),
+        ..Config::default()
+    }
+    .canvas()
+    .unwrap();
+    for seed in 0..200 {
+        let shape = canvas.random_shape(&mut seeded(seed)).unwrap();
+        for (_, fill) in shape.objects {
+            if let Some(Fill::Solid(color)) = fill {
+                assert!(matches!(color, Color::Blue | Color::Cyan), "{:?}", color);
+            }
+        }
+    }
+}
+
+#[test]
+fn palette_weights_are_followed() {
+    let canvas = Config {
+        colors: Some("blue:3,cyan:1,black:0".to_string()),
+        ..Config::default()
+    }
+    .canvas()
+    .unwrap();
+    let mut rng = seeded(0);
+    let blues = (0..4000)
+        .filter(|_| match canvas.random_color(&mut rng) {
+            Color::Blue => true,
+            Color::Cyan => false,
+            color => panic!("{:?} has no weight", color),
+        })
+        .count();
+    assert!((2800..3200).contains(&blues), "{}", blues);
+}
+
+#[test]
+fn unknown_colors_are_rejected() {
+    assert!(parse_palette("colors", "blue,teal").is_err());
+    assert!(parse_palette("colors", "blue:lots").is_err());
+    assert_eq!(
+        parse_palette("colors", " Blue:2 ,gray").unwrap(),
+        vec![(Color::Blue, 2.0), (Color::Gray, 1.0)]
+    );
+}
+
+#[test]
+fn command_line_colors_set_the_palette() {
+    let args = Args::parse(
+        ["--color", "blue:3", "--color", "cyan", "--colors", "red:0.5", "--grid-size", "3x2"].map(String::from),
+    )
+    .unwrap();
+    let mut canvas = Canvas::default_settings();
+    args.apply(&mut canvas).unwrap();
+    assert_eq!(canvas.grid_size, (3, 2));
+    assert_eq!(
+        canvas.palette,
+        vec![(Color::Blue, 3.0), (Color::Cyan, 1.0), (Color::Red, 0.5)]
+    );
+    let mut rng = seeded(0);
+    for _ in 0..200 {
+        let color = canvas.random_color(&mut rng);
+        assert!(matches!(color, Color::Blue | Color::Cyan | Color::Red), "{:?}", color);
+    }
+
+    assert!(Args::parse(["--color".to_string()]).is_err());
+    assert!(Args::parse(["--colour", "blue"].map(String::from)).is_err());
+    let args = Args::parse(["--colors", "blue,teal"].map(String::from)).unwrap();
+    assert!(args.apply(&mut Canvas::default_settings()).is_err());
+}
+
+#[test]
+fn palette_weights_adding_up_past_the_largest_number_are_rejected() {
+    let canvas = Config {
+        colors: Some("red:1e308,blue:1e308".to_string()),
+        ..Config::default()
+    }
+    .canvas()
+    .unwrap();
+    assert!(canvas.random_shape(&mut seeded(0)).is_err());
+
+    let args = Args::parse(["--colors", "red:1e308,blue:1e308"].map(String::from)).unwrap();
+    let mut canvas = Canvas::default_settings();
+    args.apply(&mut canvas).unwrap();
+    assert!(canvas.random_shape(&mut seeded(0)).is_err());
+}
@@ -0,0 +1,151 @@
+// Adjacent seeds, like the ones `lib.typ` gives to neighbouring shapes, must give
+// independent shapes. Each test builds the contingency table of some property of the
+// shape for seed `n` against the same property for seed `n + 1` and runs a chi-squared
+// test of independence on it. Everything is seeded, so these never flake.
+
+use shapemaker::canvas::Canvas;
+use shapemaker::color::Fill;
+use shapemaker::rng::{derive_seed, seed_from_string, seeded};
+use shapemaker::shape::{Object, Shape};
+
+const SAMPLES: u64 = 6000;
+
+fn shape(seed: u64) -> Shape {
+    Canvas::default_settings()
+        .random_shape(&mut seeded(seed))
+        .unwrap()
+}
+
+/// `shape_seed` of the document: unset, then set.
+const DOCUMENT_SEEDS: [&str; 2] = ["", "my-doc"];
+
+/// Seed the way `lib.typ` builds it: a small integer followed by the document seed.
+fn document_seed(n: u64, document: &str) -> u64 {
+    seed_from_string(&format!("{}{}", n, document))
+}
+
+fn kind(object: &Object) -> usize {
+    match object {
+        Object::Polygon(..) => 0,
+        Object::Line(..) => 1,
+        Object::CurveOutward(..) => 2,
+        Object::CurveInward(..) => 3,
+        Object::SmallCircle(..) => 4,
+        Object::Dot(..) => 5,
+        Object::BigCircle(..) => 6,
+    }
+}
+
+fn first_color(shape: &Shape) -> Option<usize> {
+    shape.objects.iter().find_map(|(_, fill)| match fill {
+        Some(Fill::Solid(color)) => Some(*color as usize),
+        _ => None,
+    })
+}
+
+/// Chi-squared statistic of independence and its degrees of freedom.
+fn chi_squared(pairs: &[(usize, usize)], categories: usize) -> (f64, usize) {
+    let mut table = vec![vec![0.0; categories]; categories];
+    for &(a, b) in pairs {
+        table[a][b] += 1.0;
+    }
+    let total = pairs.len() as f64;
+    let rows: Vec<f64> = table.iter().map(|row| row.iter().sum()).collect();
+    let columns: Vec<f64> = (0..categories)
+        .map(|j| table.iter().map(|row| row[j]).sum())
+        .collect();
+
+    let mut statistic = 0.0;
+    for (i, row) in table.iter().enumerate() {
+        for (j, observed) in row.iter().enumerate() {
+            let expected = rows[i] * columns[j] / total;
+            if expected > 0.0 {
+                statistic += (observed - expected).powi(2) / expected;
+            }
+        }
+    }
+    let used_rows = rows.iter().filter(|total| **total > 0.0).count();
+    let used_columns = columns.iter().filter(|total| **total > 0.0).count();
+    (statistic, (used_rows - 1) * (used_columns - 1))
+}
+
+/// Upper critical value of the chi-squared distribution at p = 0.001
+/// (Wilson–Hilferty approximation, good enough for these degrees of freedom).
+fn critical_value(degrees_of_freedom: usize) -> f64 {
+    let k = degrees_of_freedom as f64;
+    let z = 3.090;
+    k * (1.0 - 2.0 / (9.0 * k) + z * (2.0 / (9.0 * k)).sqrt()).powi(3)
+}
+
+fn assert_independent(name: &str, values: &[usize], categories: usize) {
+    let pairs: Vec<(usize, usize)> = values.windows(2).map(|w| (w[0], w[1])).collect();
+    let (statistic, degrees_of_freedom) = chi_squared(&pairs, categories);
+    assert!(
+        statistic < critical_value(degrees_of_freedom),
+        "{} of adjacent seeds are correlated: chi² = {:.1} with {} degrees of freedom",
+        name,
+        statistic,
+        degrees_of_freedom
+    );
+}
+
+#[test]
+fn adjacent_seeds_have_independent_object_counts() {
+    for document in DOCUMENT_SEEDS {
+        let counts: Vec<usize> = (0..SAMPLES)
+            .map(|n| shape(document_seed(n, document)).objects.len())
+            .collect();
+        assert_independent(&format!("object counts in {:?}", document), &counts, 8);
+    }
+}
+
+#[test]
+fn adjacent_seeds_have_independent_object_kinds() {
+    for document in DOCUMENT_SEEDS {
+        let kinds: Vec<usize> = (0..SAMPLES)
+            .map(|n| kind(&shape(document_seed(n, document)).objects[0].0))
+            .collect();
+        assert_independent(&format!("first object kinds in {:?}", document), &kinds, 7);
+    }
+}
+
+#[test]
+fn adjacent_seeds_have_independent_colors() {
+    for document in DOCUMENT_SEEDS {
+        let colors: Vec<usize> = (0..SAMPLES * 2)
+            .filter_map(|n| first_color(&shape(document_seed(n, document))))
+            .collect();
+        assert_independent(&format!("first colors in {:?}", document), &colors, 12);
+    }
+}
+
+#[test]
+fn adjacent_numeric_and_derived_seeds_are_independent() {
+    let kinds: Vec<usize> = (0..SAMPLES)
+        .map(|n| kind(&shape(n).objects[0].0))
+        .collect();
+    assert_independent("first object kinds of numeric seeds", &kinds, 7);
+
+    let kinds: Vec<usize> = (0..SAMPLES)
+        .map(|n| kind(&shape(derive_seed(42, n)).objects[0].0))
+        .collect();
+    assert_independent("first object kinds of batch seeds", &kinds, 7);
+}
+
+#[test]
+fn seeds_are_spread_out() {
+    // FNV-1a only spread a one character change towards the high bits.
+    let differing_bits = (seed_from_string("1") ^ seed_from_string("2")).count_ones();
+    assert!((16..=48).contains(&differing_bits), "{}", differing_bits);
+    assert_ne!(seed_from_string(""), seed_from_string("\0"));
+
+    for document in DOCUMENT_SEEDS {
+        let mut colors = [0; 12];
+        for n in 0..SAMPLES {
+            if let Some(color) = first_color(&shape(document_seed(n, document))) {
+                colors[color] += 1;
+            }
+        }
+        assert!(colors.iter().all(|count| *count > 0), "{:?} in {:?}", colors, document);
+    }
+}
@@ -0,0 +1,119 @@
+use shapemaker::canvas::Canvas;
+use shapemaker::color::{Color, Fill};
+use shapemaker::generation::GenerationVersion;
+use shapemaker::rng::seeded;
+use shapemaker::shape::{Anchor, Line, Object, Shape};
+use shapemaker::sketch::{Sketchy, MAX_CIRCLE_STEPS, MAX_HACHURES};
+
+fn sketchy() -> Canvas {
+    Canvas {
+        algorithm: GenerationVersion::V1_4,
+        sketchy: Some(Sketchy::default()),
+        ..Canvas::default_settings()
+    }
+}
+
+fn render(objects: Vec<(Object, Option<Fill>)>, render_seed: u64) -> String {
+    Shape {
+        objects,
+        opacities: vec![],
+        render_seed,
+    }
+    .render(&sketchy())
+    .unwrap()
+}
+
+#[test]
+fn sketches_are_seeded_by_the_shape() {
+    let canvas = sketchy();
+    for seed in 0..20 {
+        let render = || {
+            canvas
+                .random_shape(&mut seeded(seed))
+                .and_then(|shape| shape.render(&canvas))
+                .unwrap()
+        };
+        assert_eq!(render(), render());
+    }
+    let line = || vec![(Object::Line(Anchor::Point(0, 0), Anchor::Point(2, 1)), None)];
+    assert_ne!(render(line(), 1), render(line(), 2));
+
+    // The jitter comes after everything else, shapes stay the same
+    let crisp = Canvas {
+        sketchy: None,
+        ..sketchy()
+    };
+    for seed in 0..20 {
+        assert_eq!(
+            canvas.random_shape(&mut seeded(seed)).unwrap().objects,
+            crisp.random_shape(&mut seeded(seed)).unwrap().objects
+        );
+    }
+}
+
+#[test]
+fn strokes_are_doubled_and_solid_fills_hachured() {
+    let svg = render(vec![(Object::Line(Anchor::Point(0, 0), Anchor::Point(2, 0)), None)], 7);
+    assert_eq!(svg.matches('C').count(), 2, "{}", svg);
+    assert!(!svg.contains("<line"), "{}", svg);
+
+    let square = Object::Polygon(
+        Anchor::Point(0, 0),
+        vec![
+            Line::Line(Anchor::Point(2, 0)),
+            Line::Line(Anchor::Point(2, 2)),
+            Line::Line(Anchor::Point(0, 2)),
+        ],
+    );
+    let svg = render(vec![(square, Some(Fill::Solid(Color::Red)))], 7);
+    let red = Color::Red.to_string(&sketchy().colormap);
+    // 100px wide, hachures 4px apart at an angle
+    let hachures = svg.split("<path").nth(1).unwrap();
+    assert!(hachures.contains(&format!("stroke: {};", red)), "{}", svg);
+    assert!(hachures.matches('M').count() > 25, "{}", svg);
+    assert!(!svg.contains(&format!("fill: {};", red)), "{}", svg);
+
+    // Too small to hachure
+    let svg = render(vec![(Object::Dot(Anchor::Point(1, 1)), Some(Fill::Solid(Color::Red)))], 7);
+    assert!(svg.contains(&format!("fill: {}; stroke: none;", red)), "{}", svg);
+}
+
+#[test]
+fn sketchy_settings_are_validated() {
+    let mut canvas = sketchy();
+    canvas.sketchy = Some(Sketchy {
+        hachure_gap: 0.0,
+        ..Sketchy::default()
+    });
+    assert!(canvas.validate().is_err());
+    canvas.sketchy = Some(Sketchy {
+        roughness: -1.0,
+        ..Sketchy::default()
+    });
+    assert!(canvas.validate().is_err());
+}
+
+#[test]
+fn tiny_gaps_and_huge_circles_stay_bounded() {
+    let canvas = Canvas {
+        cell_size: 100_000,
+        sketchy: Some(Sketchy {
+            hachure_gap: 1e-6,
+            ..Sketchy::default()
+        }),
+        ..sketchy()
+    };
+    let svg = Shape {
+        objects: vec![(Object::BigCircle(Anchor::Cell(0, 0)), Some(Fill::Solid(Color::Red)))],
+        opacities: vec![],
+        render_seed: 7,
+    }
+    .render(&canvas)
+    .unwrap();
+    // One stroke per hachure, the circle is convex so each scanline crosses it once
+    let hachures = svg.split("<path").nth(1).unwrap();
+    assert!(hachures.matches('M').count() <= MAX_HACHURES, "{}", hachures.len());
+    // The outline is drawn twice, with a curve per step
+    let outline = svg.split("<path").nth(2).unwrap();
+    assert!(outline.matches('C').count() <= 2 * MAX_CIRCLE_STEPS as usize, "{}", outline.len());
+}
@@ -0,0 +1,168 @@
+use shapemaker::canvas::Canvas;
+use shapemaker::color::{Color, Fill};
+use shapemaker::config::Config;
+use shapemaker::generation::GenerationVersion;
+use shapemaker::shape::{Anchor, Object};
+use shapemaker::rng::seeded;
+use shapemaker::style::{BlendMode, LineCap, LineJoin, OpacityRanges, Style, StyleOverrides};
+use shapemaker::symmetry::Symmetry;
+
+fn style(canvas: &Canvas, object: &Object, fill: Option<Fill>) -> String {
+    Style::resolve(object, fill, 1.0, canvas).to_string()
+}
+
+fn line() -> Object {
+    Object::Line(Anchor::Point(0, 0), Anchor::Point(2, 2))
+}
+
+#[test]
+fn styles_default_to_the_canvas_settings() {
+    let canvas = Canvas {
+        line_width: 3.0,
+        empty_shape_stroke_width: 1.0,
+        algorithm: GenerationVersion::V1_5,
+        ..Canvas::default_settings()
+    };
+    let black = Color::Black.to_string(&canvas.colormap);
+    let red = Color::Red.to_string(&canvas.colormap);
+
+    assert_eq!(
+        style(&canvas, &line(), Some(Fill::Solid(Color::Red))),
+        format!("fill: none; stroke: {}; stroke-width: 3px;", red)
+    );
+    assert_eq!(
+        style(&canvas, &Object::BigCircle(Anchor::Cell(0, 0)), None),
+        format!("fill: none; stroke: {}; stroke-width: 1px;", black)
+    );
+    assert_eq!(
+        style(&canvas, &Object::Dot(Anchor::Point(0, 0)), Some(Fill::Solid(Color::Red))),
+        format!("fill: {};", red)
+    );
+}
+
+#[test]
+fn versions_before_1_5_keep_their_fixed_widths() {
+    let canvas = Canvas {
+        line_width: 3.0,
+        empty_shape_stroke_width: 1.0,
+        algorithm: GenerationVersion::V1_4,
+        ..Canvas::default_settings()
+    };
+    assert!(style(&canvas, &line(), None).ends_with("stroke-width: 2px;"));
+    assert!(style(&canvas, &Object::BigCircle(Anchor::Cell(0, 0)), None).ends_with("stroke-width: 0.5px;"));
+    assert!(style(&canvas, &Object::SmallCircle(Anchor::Point(0, 0)), None).ends_with("stroke-width: 1px;"));
+}
+
+#[test]
+fn overrides_replace_the_defaults_of_their_kind() {
+    let mut canvas = Canvas::default_settings();
+    canvas.object_styles.line = StyleOverrides {
+        stroke_width: Some(4.0),
+        linecap: Some(LineCap::Round),
+        linejoin: Some(LineJoin::Bevel),
+        dasharray: Some(vec![6.0, 2.5]),
+        ..StyleOverrides::default()
+    };
+    canvas.object_styles.dot.stroke = Some(Color::Blue);
+    let black = Color::Black.to_string(&canvas.colormap);
+    let blue = Color::Blue.to_string(&canvas.colormap);
+    let red = Color::Red.to_string(&canvas.colormap);
+
+    assert_eq!(
+        style(&canvas, &line(), None),
+        format!(
+            "fill: none; stroke: {}; stroke-width: 4px; stroke-linecap: round; stroke-linejoin: bevel; stroke-dasharray: 6 2.5;",
+            black
+        )
+    );
+    assert_eq!(
+        style(&canvas, &Object::Dot(Anchor::Point(0, 0)), Some(Fill::Solid(Color::Red))),
+        format!("fill: {}; stroke: {}; stroke-width: 0.5px;", red, blue)
+    );
+    assert_eq!(
+        style(&canvas, &Object::CurveInward(Anchor::Point(0, 0), Anchor::Point(2, 2)), None),
+        format!("fill: none; stroke: {}; stroke-width: 2px;", black)
+    );
+}
+
+#[test]
+fn styles_are_read_from_the_config() {
+    let config: Config = serde_json::from_str(
+        r#"{"object_styles": {"polygon": {"stroke": "black", "stroke_width": 2, "dasharray": [3, 1.5]}}}"#,
+    )
+    .unwrap();
+    let canvas = config.canvas().unwrap();
+    assert_eq!(canvas.object_styles.polygon.stroke, Some(Color::Black));
+    assert_eq!(canvas.object_styles.polygon.stroke_width, Some(2.0));
+    assert_eq!(canvas.object_styles.polygon.dasharray, Some(vec![3.0, 1.5]));
+    assert_eq!(canvas.object_styles.line, StyleOverrides::default());
+}
+
+#[test]
+fn opacities_are_drawn_from_the_range_of_their_kind() {
+    let canvas = Canvas {
+        algorithm: GenerationVersion::V1_4,
+        opacity: 0.5,
+        opacity_ranges: OpacityRanges {
+            polygon: (0.2, 0.6),
+            ..OpacityRanges::default()
+        },
+        blend_mode: BlendMode::Multiply,
+        ..Canvas::default_settings()
+    };
+    let mut drawn = vec![];
+    for seed in 0..200 {
+        let shape = canvas.random_shape(&mut seeded(seed)).unwrap();
+        assert_eq!(shape.opacities.len(), shape.objects.len());
+        for (index, (object, _)) in shape.objects.iter().enumerate() {
+            let opacity = shape.opacity(index, &canvas);
+            match object {
+                Object::Polygon(..) => {
+                    assert!((0.2..=0.6).contains(&opacity), "{}", opacity);
+                    drawn.push(opacity);
+                }
+                _ => assert_eq!(opacity, 1.0),
+            }
+        }
+    }
+    assert!(drawn.iter().any(|opacity| *opacity != drawn[0]));
+
+    let svg = canvas.random_shape(&mut seeded(3)).unwrap().render(&canvas).unwrap();
+    assert!(svg.contains("opacity: 0.5; mix-blend-mode: multiply;"), "{}", svg);
+}
+
+#[test]
+fn symmetric_copies_keep_the_opacity_of_their_original() {
+    let canvas = Canvas {
+        algorithm: GenerationVersion::V1_4,
+        symmetry: Symmetry::Both,
+        opacity_ranges: OpacityRanges {
+            polygon: (0.0, 1.0),
+            big_circle: (0.0, 1.0),
+            small_circle: (0.0, 1.0),
+            dot: (0.0, 1.0),
+            curve_inward: (0.0, 1.0),
+            curve_outward: (0.0, 1.0),
+            line: (0.0, 1.0),
+        },
+        ..Canvas::default_settings()
+    };
+    for seed in 0..50 {
+        let shape = canvas.random_shape(&mut seeded(seed)).unwrap();
+        assert_eq!(shape.opacities.len(), shape.objects.len());
+        let mut opacities = shape.opacities.clone();
+        opacities.sort_by(f32::total_cmp);
+        opacities.dedup();
+        assert!(opacities.len() <= canvas.objects_count_range.end, "{:?}", shape.opacities);
+    }
+}
+
+#[test]
+fn opacities_outside_of_0_and_1_are_rejected() {
+    let mut canvas = Canvas::default_settings();
+    canvas.opacity_ranges.dot = (0.8, 0.2);
+    assert!(canvas.validate().is_err());
+    canvas.opacity_ranges.dot = (0.2, 0.8);
+    canvas.opacity = 1.5;
+    assert!(canvas.validate().is_err());
+}
@@ -0,0 +1,173 @@
+use shapemaker::canvas::Canvas;
+use shapemaker::generation::GenerationVersion;
+use shapemaker::rng::seeded;
+use shapemaker::shape::{Anchor, Object, Shape};
+use shapemaker::symmetry::Symmetry;
+
+type Point = (i64, i64);
+
+/// What an object looks like: its kind and pixel points, with the control point of curves.
+fn drawn(object: &Object, canvas: &Canvas) -> (&'static str, Vec<Point>) {
+    let rounded = |(x, y): (f32, f32)| ((x * 2.0).round() as i64, (y * 2.0).round() as i64);
+    let mut points: Vec<Point> = object.points(canvas).into_iter().map(rounded).collect();
+    let kind = match object {
+        Object::Polygon(..) => "polygon",
+        Object::Line(..) => "line",
+        Object::CurveInward(..) | Object::CurveOutward(..) => {
+            let (start, end) = (object.points(canvas)[0], object.points(canvas)[1]);
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let side = if dx != 0.0 { dx.signum() } else { -dy.signum() };
+            let bulge = match object {
+                Object::CurveInward(..) => -side,
+                _ => side,
+            } * canvas.curvature;
+            points.push(rounded((
+                (start.0 + end.0) / 2.0 - bulge * dy,
+                (start.1 + end.1) / 2.0 + bulge * dx,
+            )));
+            "curve"
+        }
+        Object::SmallCircle(..) => "small circle",
+        Object::Dot(..) => "dot",
+        Object::BigCircle(..) => "big circle",
+    };
+    if kind != "polygon" && kind != "curve" {
+        points.sort();
+    }
+    if kind == "curve" {
+        points[..2].sort();
+    }
+    (kind, points)
+}
+
+fn transforms(symmetry: Symmetry) -> Vec<fn(Point, i64) -> Point> {
+    let mirror_x: fn(Point, i64) -> Point = |(x, y), size| (size - x, y);
+    let mirror_y: fn(Point, i64) -> Point = |(x, y), size| (x, size - y);
+    let half_turn: fn(Point, i64) -> Point = |(x, y), size| (size - x, size - y);
+    let quarter_turn: fn(Point, i64) -> Point = |(x, y), size| (size - y, x);
+    match symmetry {
+        Symmetry::None => vec![],
+        Symmetry::Horizontal => vec![mirror_x],
+        Symmetry::Vertical => vec![mirror_y],
+        Symmetry::Both => vec![mirror_x, mirror_y],
+        Symmetry::Rotational2 => vec![half_turn],
+        Symmetry::Rotational4 => vec![quarter_turn],
+    }
+}
+
+fn canvas(symmetry: Symmetry, size: usize) -> Canvas {
+    Canvas {
+        grid_size: (size, size),
+        algorithm: GenerationVersion::V1_4,
+        symmetry,
+        ..Canvas::default_settings()
+    }
+}
+
+#[test]
+fn shapes_are_unchanged_by_their_symmetry() {
+    for symmetry in [
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Both,
+        Symmetry::Rotational2,
+        Symmetry::Rotational4,
+    ] {
+        for (size, algorithm) in [
+            (3, GenerationVersion::V1_4),
+            (4, GenerationVersion::V1_4),
+            (5, GenerationVersion::V1_4),
+            (3, GenerationVersion::V1_1),
+            (4, GenerationVersion::V1_1),
+        ] {
+            let canvas = Canvas {
+                algorithm,
+                ..canvas(symmetry, size)
+            };
+            // Pixel coordinates are doubled by `drawn`
+            let extent = 2 * (size as i64 - 1) * canvas.cell_size as i64;
+            for seed in 0..50 {
+                let shape = canvas.random_shape(&mut seeded(seed)).unwrap();
+                let drawn_objects: Vec<_> =
+                    shape.objects.iter().map(|(object, _)| drawn(object, &canvas)).collect();
+                for (kind, points) in &drawn_objects {
+                    for transform in transforms(symmetry) {
+                        let mut moved: Vec<Point> =
+                            points.iter().map(|point| transform(*point, extent)).collect();
+                        if *kind != "polygon" && *kind != "curve" {
+                            moved.sort();
+                        }
+                        if *kind == "curve" {
+                            moved[..2].sort();
+                        }
+                        assert!(
+                            drawn_objects.iter().any(|(k, p)| k == kind && *p == moved),
+                            "{:?} on {}x{} with {:?}, seed {}: no {} at {:?}",
+                            symmetry,
+                            size,
+                            size,
+                            algorithm,
+                            seed,
+                            kind,
+                            moved
+                        );
+                    }
+                }
+                shape.render(&canvas).unwrap();
+            }
+        }
+    }
+}
+
+#[test]
+fn copies_on_top_of_each_other_are_left_out() {
+    let count = |symmetry, object: Object| {
+        let canvas = canvas(symmetry, 3);
+        let shape = Shape {
+            objects: vec![(object, None)],
+            opacities: vec![],
+            render_seed: 0,
+        };
+        symmetry.apply(shape, &canvas).objects.len()
+    };
+    assert_eq!(count(Symmetry::Both, Object::Dot(Anchor::Center)), 1);
+    assert_eq!(count(Symmetry::Both, Object::Dot(Anchor::Point(0, 0))), 4);
+    assert_eq!(count(Symmetry::Both, Object::Dot(Anchor::Point(1, 0))), 2);
+    assert_eq!(count(Symmetry::Rotational4, Object::Dot(Anchor::Point(1, 0))), 4);
+    assert_eq!(
+        count(Symmetry::Horizontal, Object::Line(Anchor::Point(0, 0), Anchor::Point(2, 0))),
+        1
+    );
+}
+
+#[test]
+fn quarter_turns_need_a_square_grid() {
+    let canvas = Canvas {
+        grid_size: (3, 4),
+        ..canvas(Symmetry::Rotational4, 3)
+    };
+    assert!(canvas.random_shape(&mut seeded(0)).is_err());
+}
+
+#[test]
+fn the_center_of_older_versions_is_mirrored() {
+    let canvas = Canvas {
+        algorithm: GenerationVersion::V1_1,
+        ..canvas(Symmetry::Horizontal, 3)
+    };
+    let shape = Shape {
+        objects: vec![(Object::BigCircle(Anchor::Center), None)],
+        opacities: vec![],
+        render_seed: 0,
+    };
+    let objects: Vec<Object> = Symmetry::Horizontal
+        .apply(shape, &canvas)
+        .objects
+        .into_iter()
+        .map(|(object, _)| object)
+        .collect();
+    assert_eq!(
+        objects,
+        vec![Object::BigCircle(Anchor::Cell(0, 0)), Object::BigCircle(Anchor::Cell(1, 0))]
+    );
+}
@@ -0,0 +1,17 @@
+use shapemaker::canvas::{Canvas, ObjectWeights};
+use shapemaker::error::Error;
+use shapemaker::rng::seeded;
+
+#[test]
+fn weights_adding_up_past_the_largest_number_are_rejected() {
+    let canvas = Canvas {
+        object_weights: ObjectWeights {
+            polygon: 1e308,
+            big_circle: 1e308,
+            ..ObjectWeights::default()
+        },
+        ..Canvas::default_settings()
+    };
+    assert!(matches!(canvas.validate(), Err(Error::InvalidParameter { .. })));
+    assert!(canvas.random_shape(&mut seeded(0)).is_err());
+}
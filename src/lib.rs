@@ -1,16 +1,280 @@
-
 use wasm_minimal_protocol::*;
 initiate_protocol!();
 
-use ciborium::{de::from_reader, ser::into_writer};
+use ciborium::{de::from_reader, ser::into_writer, value::Value};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use shapemaker::{
-    getrandom_custom::{set_random_seed_from_string},
+    rng::{seed_from_string, seeded},
     canvas::*,
+    color::Fill,
     color_mapping::*,
+    config::Config,
+    error::{Error, Result},
+    generation::GenerationVersion,
+    shape::{Object, Shape},
     Args
 };
 
+/// Generates a shape from a single CBOR-encoded `Config` map.
+#[wasm_func]
+pub fn generate(config: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    fn generate(config: &[u8]) -> Result<Vec<u8>> {
+        let config: Config = decode("config", config)?;
+        let canvas = config.canvas()?;
+
+        Ok(canvas
+            .random_shape(&mut config.rng())?
+            .render(&canvas)?
+            .into_bytes())
+    }
+
+    generate(config).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize)]
+struct ShapeData<'a> {
+    seed: u64,
+    /// `[x, y, width, height]` of the SVG, points below are in the same space.
+    view_box: (i32, i32, usize, usize),
+    objects: Vec<ObjectData<'a>>,
+    render_seed: u64,
+}
+
+#[derive(Serialize)]
+struct ObjectData<'a> {
+    object: &'a Object,
+    fill: Option<Fill>,
+    opacity: f32,
+    points: Vec<(f32, f32)>,
+}
+
+/// Generates a shape like `generate`, but returns it as CBOR data instead of SVG:
+/// every object with its anchors, their pixel coordinates and fill, plus the seed.
+#[wasm_func]
+pub fn shape_data(config: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    fn shape_data(config: &[u8]) -> Result<Vec<u8>> {
+        let config: Config = decode("config", config)?;
+        let seed = config.seed();
+        let canvas = config.canvas()?;
+        let shape = canvas.random_shape(&mut seeded(seed))?;
+        let (width, height) = canvas.dimensions();
+        let padding = -(canvas.canvas_outter_padding as i32);
+
+        encode(&ShapeData {
+            seed,
+            view_box: (padding, padding, width, height),
+            objects: shape
+                .objects
+                .iter()
+                .enumerate()
+                .map(|(index, (object, fill))| ObjectData {
+                    object,
+                    fill: *fill,
+                    opacity: shape.opacity(index, &canvas),
+                    points: object.points(&canvas),
+                })
+                .collect(),
+            render_seed: shape.render_seed,
+        })
+    }
+
+    shape_data(config).map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+struct ShapeDescription {
+    #[serde(default)]
+    config: Config,
+    objects: Vec<ObjectDescription>,
+    /// Drawn from the config's seed when missing.
+    render_seed: Option<u64>,
+}
+
+/// Same layout as `ObjectData`, so `shape_data` objects can be passed back as-is.
+#[derive(Deserialize)]
+struct ObjectDescription {
+    object: Object,
+    #[serde(default)]
+    fill: Option<Fill>,
+    /// The lower end of the kind's opacity range when missing.
+    #[serde(default, deserialize_with = "shapemaker::config::float")]
+    opacity: Option<f32>,
+}
+
+/// Renders the objects of a CBOR shape description (`objects` plus the canvas
+/// `config`) as SVG, without any randomness.
+#[wasm_func]
+pub fn render(description: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    fn render(description: &[u8]) -> Result<Vec<u8>> {
+        let description: ShapeDescription = decode("description", description)?;
+        let canvas = description.config.canvas()?;
+        let objects: Vec<(Object, Option<Fill>)> = description
+            .objects
+            .iter()
+            .map(|ObjectDescription { object, fill, .. }| (object.clone(), *fill))
+            .collect();
+        let opacities = description
+            .objects
+            .iter()
+            .zip(&objects)
+            .map(|(ObjectDescription { opacity, .. }, (object, _))| {
+                opacity.unwrap_or(canvas.opacity_ranges.of(object).0)
+            })
+            .collect();
+        let render_seed = description
+            .render_seed
+            .unwrap_or_else(|| canvas.random_render_seed(&mut description.config.rng()));
+        let shape = Shape {
+            objects,
+            opacities,
+            render_seed,
+        };
+
+        Ok(shape.render(&canvas)?.into_bytes())
+    }
+
+    render(description).map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+struct BatchRequest {
+    #[serde(default)]
+    config: Config,
+    count: usize,
+    /// Regenerate shapes until no two of them have the same structure.
+    #[serde(default)]
+    unique: bool,
+}
+
+/// Generates `count` shapes from the config's seed and returns them as a CBOR array of SVGs.
+/// They are all drawn on the same canvas, so they share the theme and every setting,
+/// and each one gets its own seed derived from the base seed and its position,
+/// see `Canvas::random_batch`, which also caps `count`.
+#[wasm_func]
+pub fn generate_batch(request: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    fn generate_batch(request: &[u8]) -> Result<Vec<u8>> {
+        let request: BatchRequest = decode("request", request)?;
+        let seed = request.config.seed();
+        let canvas = request.config.canvas()?;
+
+        let shapes = canvas.random_batch(seed, request.count, request.unique)?;
+
+        let svgs = shapes
+            .into_iter()
+            .map(|shape| shape.render(&canvas))
+            .collect::<Result<Vec<String>>>()?;
+        encode(&svgs)
+    }
+
+    generate_batch(request).map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+struct RerollRequest {
+    #[serde(default)]
+    config: Config,
+    /// How many times each object, by index, is re-rolled. Missing objects are not.
+    #[serde(default)]
+    rolls: Vec<u64>,
+    /// Objects to keep as they are in `rolls`, every other one is drawn as its `roll`-th variant.
+    #[serde(default)]
+    lock: Option<Vec<usize>>,
+    /// Same default as `reroll_shape`, the 0th variant would be the shape itself.
+    #[serde(default = "first_reroll")]
+    roll: u64,
+}
+
+fn first_reroll() -> u64 {
+    1
+}
+
+/// Draws the shape of `config` again with some of its objects re-rolled, either the ones
+/// given in `rolls`, or every object that is not in `lock`. Needs algorithm 1.2 or later.
+#[wasm_func]
+pub fn reroll(request: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    fn reroll(request: &[u8]) -> Result<Vec<u8>> {
+        let request: RerollRequest = decode("request", request)?;
+        let canvas = request.config.canvas()?;
+        let roll = |index: usize| match &request.lock {
+            Some(lock) if !lock.contains(&index) => request.roll,
+            _ => request.rolls.get(index).copied().unwrap_or(0),
+        };
+
+        Ok(canvas
+            .random_shape_rolled(&mut request.config.rng(), roll)?
+            .render(&canvas)?
+            .into_bytes())
+    }
+
+    reroll(request).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize)]
+struct Info {
+    version: &'static str,
+    shapemaker_version: &'static str,
+    objects: &'static [&'static str],
+    fills: &'static [&'static str],
+    algorithms: Vec<&'static str>,
+    themes: Value,
+    /// `{name: {type, default}}` for every `Config` field.
+    parameters: Value,
+}
+
+/// What this build of the plugin supports, as CBOR, so that wrappers can feature-detect.
+#[wasm_func]
+pub fn info() -> std::result::Result<Vec<u8>, String> {
+    fn info() -> Result<Vec<u8>> {
+        fn value<T: Serialize>(value: &T) -> Result<Value> {
+            Value::serialized(value).map_err(|e| Error::Encode(e.to_string()))
+        }
+
+        let themes = ColorMapping::BUILTIN
+            .iter()
+            .filter_map(|name| ColorMapping::builtin(name).map(|colormap| (name, colormap)))
+            .map(|(name, colormap)| Ok((Value::from(*name), value(&colormap)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let defaults = match value(&Config::defaults())? {
+            Value::Map(defaults) => defaults,
+            _ => vec![],
+        };
+        let parameters = Config::PARAMETERS
+            .iter()
+            .map(|(name, kind)| {
+                let default = defaults
+                    .iter()
+                    .find(|(key, _)| key.as_text() == Some(name))
+                    .map(|(_, default)| default.clone())
+                    .unwrap_or(Value::Null);
+                (
+                    Value::from(*name),
+                    Value::Map(vec![
+                        (Value::from("type"), Value::from(*kind)),
+                        (Value::from("default"), default),
+                    ]),
+                )
+            })
+            .collect();
+
+        encode(&Info {
+            version: env!("CARGO_PKG_VERSION"),
+            shapemaker_version: shapemaker::VERSION,
+            objects: &Object::KINDS,
+            fills: &Fill::KINDS,
+            algorithms: GenerationVersion::ALL.iter().map(|version| version.name()).collect(),
+            themes: Value::Map(themes),
+            parameters: Value::Map(parameters),
+        })
+    }
+
+    info().map_err(|e| e.to_string())
+}
+
+/// Positional entry point, kept so that older `lib.typ` versions still work.
+/// New options only go through `generate`.
+#[allow(clippy::too_many_arguments)]
 #[wasm_func]
 pub fn svg(
     grid_size: &[u8],
@@ -25,110 +289,94 @@ pub fn svg(
     polygon_vertices: &[u8],
     seed: &[u8],
     mapping: &[u8],
-) -> Vec<u8> {
+) -> std::result::Result<Vec<u8>, String> {
 
-    set_random_seed_from_string(&String::from_utf8_lossy(seed));
-    
-    // Helper function to parse &[u8] to various types
-    fn parse_bytes_to_string(bytes: &[u8]) -> Result<String, std::str::Utf8Error> {
-        std::str::from_utf8(bytes).map(|s| s.to_string())
-    }
-    
-    fn parse_bytes_to_usize(bytes: &[u8]) -> Result<usize, Box<dyn std::error::Error>> {
-        let s = std::str::from_utf8(bytes)?;
-        Ok(s.parse::<usize>()?)
+    let seed = seed_from_string(&String::from_utf8_lossy(seed));
+
+    // Helper functions to parse &[u8] to various types, empty means unset
+    fn parse_bytes_to_string(parameter: &str, bytes: &[u8]) -> Result<Option<String>> {
+        let s = std::str::from_utf8(bytes)
+            .map_err(|e| Error::invalid(parameter, e.to_string()))?;
+        Ok(if s.is_empty() { None } else { Some(s.to_string()) })
     }
-    
-    fn parse_bytes_to_f32(bytes: &[u8]) -> Result<f32, Box<dyn std::error::Error>> {
-        let s = std::str::from_utf8(bytes)?;
-        Ok(s.parse::<f32>()?)
+
+    fn parse_bytes<T: std::str::FromStr>(parameter: &str, bytes: &[u8]) -> Result<Option<T>>
+    where
+        T::Err: std::fmt::Display,
+    {
+        match parse_bytes_to_string(parameter, bytes)? {
+            Some(s) => s
+                .parse::<T>()
+                .map(Some)
+                .map_err(|e| Error::invalid(parameter, format!("{:?}: {}", s, e))),
+            None => Ok(None),
+        }
     }
 
-    fn parse_bytes_to_bool(bytes: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
-        let s = std::str::from_utf8(bytes)?;
-        match s.to_lowercase().as_str() {
-            "true" | "1" | "yes" => Ok(true),
-            "false" | "0" | "no" => Ok(false),
-            _ => Err(format!("Invalid boolean value: {}", s).into()),
+    fn parse_bytes_to_bool(parameter: &str, bytes: &[u8]) -> Result<bool> {
+        match parse_bytes_to_string(parameter, bytes)? {
+            Some(s) => match s.to_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(true),
+                "false" | "0" | "no" => Ok(false),
+                _ => Err(Error::invalid(parameter, format!("invalid boolean value: {:?}", s))),
+            },
+            None => Ok(false),
         }
     }
-    
-    let parsed_grid_size = parse_bytes_to_string(grid_size).unwrap_or_default();
-    let parsed_cell_size = parse_bytes_to_usize(cell_size).ok();
-    let parsed_canvas_padding = parse_bytes_to_usize(canvas_padding).ok();
-    let parsed_line_width = parse_bytes_to_f32(line_width).ok();
-    let parsed_small_circle_radius = parse_bytes_to_f32(small_circle_radius).ok();
-    let parsed_dot_radius = parse_bytes_to_f32(dot_radius).ok();
-    let parsed_empty_shape_stroke = parse_bytes_to_f32(empty_shape_stroke).ok();
-    let parsed_render_grid = parse_bytes_to_bool(render_grid).unwrap_or(false);
-    let parsed_objects_count = parse_bytes_to_string(objects_count).unwrap_or_default();
-    let parsed_polygon_vertices = parse_bytes_to_string(polygon_vertices).unwrap_or_default();
-
-    let args = Args {
-        flag_version: false,
-        flag_color: vec![],
-        flag_colors: None,
-        flag_grid_size: if parsed_grid_size.is_empty() { None } else { Some(parsed_grid_size) },
-        flag_cell_size: parsed_cell_size,
-        flag_canvas_padding: parsed_canvas_padding,
-        flag_line_width: parsed_line_width,
-        flag_small_circle_radius: parsed_small_circle_radius,
-        flag_dot_radius: parsed_dot_radius,
-        flag_empty_shape_stroke: parsed_empty_shape_stroke,
-        flag_render_grid: parsed_render_grid,
-        flag_objects_count: if parsed_objects_count.is_empty() { None } else { Some(parsed_objects_count) },
-        flag_polygon_vertices: if parsed_polygon_vertices.is_empty() { None } else { Some(parsed_polygon_vertices) },
-    };
 
-    let colormap: ColorMapping = from_reader(mapping).unwrap();
+    let svg = || -> Result<Vec<u8>> {
+        let args = Args {
+            flag_version: false,
+            flag_color: vec![],
+            flag_colors: None,
+            flag_grid_size: parse_bytes_to_string("grid_size", grid_size)?,
+            flag_cell_size: parse_bytes("cell_size", cell_size)?,
+            flag_canvas_padding: parse_bytes("canvas_padding", canvas_padding)?,
+            flag_line_width: parse_bytes("line_width", line_width)?,
+            flag_small_circle_radius: parse_bytes("small_circle_radius", small_circle_radius)?,
+            flag_dot_radius: parse_bytes("dot_radius", dot_radius)?,
+            flag_empty_shape_stroke: parse_bytes("empty_shape_stroke", empty_shape_stroke)?,
+            flag_render_grid: parse_bytes_to_bool("render_grid", render_grid)?,
+            flag_objects_count: parse_bytes_to_string("objects_count", objects_count)?,
+            flag_polygon_vertices: parse_bytes_to_string("polygon_vertices", polygon_vertices)?,
+        };
+
+        let colormap: ColorMapping = decode("mapping", mapping)?;
 
-    let mut canvas = Canvas::default_settings();
-    set_canvas_settings_from_args(&args, &mut canvas);
-    canvas.colormap = colormap.clone();
-    
-    canvas
-        .random_shape()
-        .render(&canvas)
-        .into_bytes()
+        let mut canvas = Canvas::default_settings();
+        args.apply(&mut canvas)?;
+        canvas.colormap = colormap.clone();
+
+        Ok(canvas
+            .random_shape(&mut seeded(seed))?
+            .render(&canvas)?
+            .into_bytes())
+    };
+
+    svg().map_err(|e| e.to_string())
 }
 
+/// Decodes a CBOR plugin argument, `input` names it in the error message.
+fn decode<T: DeserializeOwned>(input: &str, bytes: &[u8]) -> Result<T> {
+    from_reader(bytes).map_err(|e| {
+        Error::decode(
+            input,
+            match e {
+                ciborium::de::Error::Semantic(_, reason) => reason,
+                ciborium::de::Error::Syntax(offset) => {
+                    format!("malformed CBOR at byte {}", offset)
+                }
+                ciborium::de::Error::Io(e) => format!("{:?}", e),
+                ciborium::de::Error::RecursionLimitExceeded => {
+                    "nested too deeply".to_string()
+                }
+            },
+        )
+    })
+}
 
-pub fn set_canvas_settings_from_args(args: &Args, canvas: &mut Canvas) {
-    if let Some(dimensions) = &args.flag_grid_size {
-        let mut split = dimensions.split('x');
-        let width = split.next().unwrap().parse::<usize>().unwrap();
-        let height = split.next().unwrap().parse::<usize>().unwrap();
-        canvas.grid_size = (width, height);
-    }
-    if let Some(cell_size) = args.flag_cell_size {
-        canvas.cell_size = cell_size;
-    }
-    if let Some(canvas_padding) = args.flag_canvas_padding {
-        canvas.canvas_outter_padding = canvas_padding;
-    }
-    if let Some(line_width) = args.flag_line_width {
-        canvas.line_width = line_width;
-    }
-    if let Some(small_circle_radius) = args.flag_small_circle_radius {
-        canvas.small_circle_radius = small_circle_radius;
-    }
-    if let Some(dot_radius) = args.flag_dot_radius {
-        canvas.dot_radius = dot_radius;
-    }
-    if let Some(empty_shape_stroke) = args.flag_empty_shape_stroke {
-        canvas.empty_shape_stroke_width = empty_shape_stroke;
-    }
-    if let Some(objects_count) = &args.flag_objects_count {
-        let mut split = objects_count.split("..");
-        let min = split.next().unwrap().parse::<usize>().unwrap();
-        let max = split.next().unwrap().parse::<usize>().unwrap();
-        // +1 because the range is exclusive, using ..= raises a type error
-        canvas.objects_count_range = min..(max + 1);
-    }
-    if let Some(polygon_vertices) = &args.flag_polygon_vertices {
-        let mut split = polygon_vertices.split("..");
-        let min = split.next().unwrap().parse::<usize>().unwrap();
-        let max = split.next().unwrap().parse::<usize>().unwrap();
-        canvas.polygon_vertices_range = min..(max + 1);
-    }
-}
\ No newline at end of file
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    into_writer(value, &mut bytes).map_err(|e| Error::Encode(e.to_string()))?;
+    Ok(bytes)
+}